@@ -1,11 +1,52 @@
 use {
 	super::{InfixOperator, PrefixOperator},
-	crate::Statement,
+	crate::{Span, Statement},
+	num_bigint::BigInt,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Expression {
+/// An expression together with its location in the source code.
+///
+/// The span does not participate in equality checks, so two expressions compare equal if they
+/// have the same structure, no matter where they came from.
+#[derive(Debug, Clone)]
+pub struct Expression {
+	pub kind: ExpressionKind,
+	pub span: Span,
+}
+
+impl Expression {
+	pub fn new(kind: ExpressionKind, span: Span) -> Self {
+		Self { kind, span }
+	}
+
+	/// Whether the expression refers to a variable or an element of one, like `a` or `a[0]`,
+	/// which can be assigned to.
+	pub fn is_assignable(&self) -> bool {
+		match &self.kind {
+			ExpressionKind::Identifier(_) => true,
+			ExpressionKind::Index { lhs, .. } => lhs.is_assignable(),
+			_ => false,
+		}
+	}
+}
+
+impl PartialEq for Expression {
+	fn eq(&self, other: &Self) -> bool {
+		self.kind == other.kind
+	}
+}
+
+impl From<ExpressionKind> for Expression {
+	fn from(kind: ExpressionKind) -> Self {
+		Self::new(kind, Span::default())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionKind {
 	Int(i64),
+	BigInt(BigInt),
+	Float(f64),
 	Bool(bool),
 	Identifier(String),
 	String(String),
@@ -16,6 +57,29 @@ pub enum Expression {
 		consequence: Vec<Statement>,
 		alternative: Option<Vec<Statement>>,
 	},
+	While {
+		condition: Box<Expression>,
+		body: Vec<Statement>,
+	},
+	/// `for (x in iterable)`, or `for (k, v in iterable)` with two variables.
+	For {
+		variables: Vec<String>,
+		iterable: Box<Expression>,
+		body: Vec<Statement>,
+	},
+	/// `try { body } catch (variable) { handler }`, optionally followed by `finally { finally }`.
+	Try {
+		body: Vec<Statement>,
+		variable: String,
+		handler: Vec<Statement>,
+		finally: Option<Vec<Statement>>,
+	},
+	/// `start..end`, or `start..=end` if `inclusive`.
+	Range {
+		start: Box<Expression>,
+		end: Box<Expression>,
+		inclusive: bool,
+	},
 	Function {
 		parameters: Vec<String>,
 		body: Vec<Statement>,
@@ -37,40 +101,64 @@ pub enum Expression {
 		lhs: Box<Expression>,
 		idx: Box<Expression>,
 	},
+	/// `value?`, which returns `value` from the current function if it is an error.
+	Propagate {
+		value: Box<Expression>,
+	},
+	/// `target = value`, or `target += value` etc. if there is an `operator`.
+	Assign {
+		target: Box<Expression>,
+		operator: Option<InfixOperator>,
+		value: Box<Expression>,
+	},
 }
 
 impl From<i64> for Expression {
 	fn from(value: i64) -> Self {
-		Self::Int(value)
+		ExpressionKind::Int(value).into()
+	}
+}
+
+impl From<f64> for Expression {
+	fn from(value: f64) -> Self {
+		ExpressionKind::Float(value).into()
 	}
 }
 
 impl From<bool> for Expression {
 	fn from(value: bool) -> Self {
-		Self::Bool(value)
+		ExpressionKind::Bool(value).into()
 	}
 }
 
 impl From<&str> for Expression {
 	fn from(value: &str) -> Self {
-		Self::Identifier(value.to_owned())
+		ExpressionKind::Identifier(value.to_owned()).into()
 	}
 }
 
 impl From<String> for Expression {
 	fn from(value: String) -> Self {
-		Self::Identifier(value.to_owned())
+		ExpressionKind::Identifier(value).into()
 	}
 }
 
 impl std::fmt::Display for Expression {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.kind)
+	}
+}
+
+impl std::fmt::Display for ExpressionKind {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
-			Expression::Int(int) => write!(f, "{int}"),
-			Expression::Bool(bool) => write!(f, "{bool}"),
-			Expression::Identifier(identifier) => write!(f, "{identifier}"),
-			Expression::String(string) => write!(f, "\"{string}\""),
-			Expression::Array(array) => write!(
+			ExpressionKind::Int(int) => write!(f, "{int}"),
+			ExpressionKind::BigInt(int) => write!(f, "{int}"),
+			ExpressionKind::Float(float) => write!(f, "{float:?}"),
+			ExpressionKind::Bool(bool) => write!(f, "{bool}"),
+			ExpressionKind::Identifier(identifier) => write!(f, "{identifier}"),
+			ExpressionKind::String(string) => write!(f, "\"{string}\""),
+			ExpressionKind::Array(array) => write!(
 				f,
 				"[{}]",
 				array
@@ -79,16 +167,16 @@ impl std::fmt::Display for Expression {
 					.collect::<Vec<_>>()
 					.join(", ")
 			),
-			Expression::Map(pairs) => {
-				write!(f, "{{\n")?;
+			ExpressionKind::Map(pairs) => {
+				writeln!(f, "{{")?;
 
 				for (k, v) in pairs {
-					write!(f, "  {k} => {v},\n")?;
+					writeln!(f, "  {k} => {v},")?;
 				}
 
 				write!(f, "}}")
 			}
-			Expression::Condition { condition, consequence, alternative } => {
+			ExpressionKind::Condition { condition, consequence, alternative } => {
 				write!(f, "if ({condition}) {{ ")?;
 
 				for statement in consequence {
@@ -109,7 +197,54 @@ impl std::fmt::Display for Expression {
 
 				Ok(())
 			}
-			Expression::Function { parameters, body } => {
+			ExpressionKind::While { condition, body } => {
+				write!(f, "while ({condition}) {{ ")?;
+
+				for statement in body {
+					write!(f, "{statement}")?;
+				}
+
+				write!(f, " }}")
+			}
+			ExpressionKind::For { variables, iterable, body } => {
+				write!(f, "for ({} in {iterable}) {{ ", variables.join(", "))?;
+
+				for statement in body {
+					write!(f, "{statement}")?;
+				}
+
+				write!(f, " }}")
+			}
+			ExpressionKind::Try { body, variable, handler, finally } => {
+				write!(f, "try {{ ")?;
+
+				for statement in body {
+					write!(f, "{statement}")?;
+				}
+
+				write!(f, " }} catch ({variable}) {{ ")?;
+
+				for statement in handler {
+					write!(f, "{statement}")?;
+				}
+
+				write!(f, " }}")?;
+
+				if let Some(finally) = finally {
+					write!(f, " finally {{ ")?;
+
+					for statement in finally {
+						write!(f, "{statement}")?;
+					}
+
+					write!(f, " }}")?;
+				}
+
+				Ok(())
+			}
+			ExpressionKind::Range { start, end, inclusive: false } => write!(f, "({start}..{end})"),
+			ExpressionKind::Range { start, end, inclusive: true } => write!(f, "({start}..={end})"),
+			ExpressionKind::Function { parameters, body } => {
 				write!(f, "fn ({}) {{ ", parameters.join(", "))?;
 
 				for statement in body {
@@ -118,7 +253,7 @@ impl std::fmt::Display for Expression {
 
 				write!(f, " }}")
 			}
-			Expression::Call { function, arguments } => {
+			ExpressionKind::Call { function, arguments } => {
 				let arguments = arguments
 					.iter()
 					.map(|arg| arg.to_string())
@@ -127,9 +262,16 @@ impl std::fmt::Display for Expression {
 
 				write!(f, "{function}({arguments})")
 			}
-			Expression::Prefix { operator, rhs } => write!(f, "({operator}{rhs})"),
-			Expression::Infix { operator, lhs, rhs } => write!(f, "({lhs} {operator} {rhs})"),
-			Expression::Index { lhs, idx } => write!(f, "({lhs}[{idx}])"),
+			ExpressionKind::Prefix { operator, rhs } => write!(f, "({operator}{rhs})"),
+			ExpressionKind::Infix { operator, lhs, rhs } => write!(f, "({lhs} {operator} {rhs})"),
+			ExpressionKind::Index { lhs, idx } => write!(f, "({lhs}[{idx}])"),
+			ExpressionKind::Propagate { value } => write!(f, "({value}?)"),
+			ExpressionKind::Assign { target, operator: None, value } => {
+				write!(f, "({target} = {value})")
+			}
+			ExpressionKind::Assign { target, operator: Some(operator), value } => {
+				write!(f, "({target} {operator}= {value})")
+			}
 		}
 	}
 }
@@ -2,10 +2,10 @@
 mod tests;
 
 mod statement;
-pub use statement::Statement;
+pub use statement::{Statement, StatementKind};
 
 mod expression;
-pub use expression::Expression;
+pub use expression::{Expression, ExpressionKind};
 
 mod operators;
 pub use operators::{InfixOperator, PrefixOperator};
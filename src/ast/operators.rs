@@ -1,7 +1,4 @@
-use {
-	crate::Token,
-	color_eyre::{eyre::bail as yeet, Result},
-};
+use crate::{parser::ParseError, yeet, Token, TokenKind};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PrefixOperator {
@@ -19,14 +16,14 @@ impl std::fmt::Display for PrefixOperator {
 }
 
 impl TryFrom<&Token> for PrefixOperator {
-	type Error = color_eyre::Report;
+	type Error = ParseError;
 
 	#[tracing::instrument(level = "TRACE", ret)]
-	fn try_from(token: &Token) -> Result<Self> {
-		Ok(match token {
-			Token::Bang => Self::Not,
-			Token::Minus => Self::Neg,
-			token => yeet!("`{token:?}` is not a valid prefix operator"),
+	fn try_from(token: &Token) -> Result<Self, ParseError> {
+		Ok(match token.kind {
+			TokenKind::Bang => Self::Not,
+			TokenKind::Minus => Self::Neg,
+			_ => yeet!(ParseError::InvalidPrefixOperator { token: token.clone() }),
 		})
 	}
 }
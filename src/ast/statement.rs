@@ -1,34 +1,71 @@
-use crate::Expression;
+use crate::{Expression, Span};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Statement {
-	Let { name: String, value: Expression },
-	Return { value: Expression },
-	Expression(Expression),
-	Block(Vec<Statement>),
+/// A statement together with its location in the source code.
+///
+/// Like with [`Expression`], the span does not participate in equality checks.
+#[derive(Debug, Clone)]
+pub struct Statement {
+	pub kind: StatementKind,
+	pub span: Span,
 }
 
 impl Statement {
+	pub fn new(kind: StatementKind, span: Span) -> Self {
+		Self { kind, span }
+	}
+
 	pub fn r#let(name: impl Into<String>, value: impl Into<Expression>) -> Self {
-		Self::Let { name: name.into(), value: value.into() }
+		StatementKind::Let { name: name.into(), value: value.into() }.into()
 	}
 
 	pub fn r#return(value: impl Into<Expression>) -> Self {
-		Self::Return { value: value.into() }
+		StatementKind::Return { value: value.into() }.into()
 	}
 
 	pub fn expression(expression: impl Into<Expression>) -> Self {
-		Self::Expression(expression.into())
+		StatementKind::Expression(expression.into()).into()
+	}
+}
+
+impl PartialEq for Statement {
+	fn eq(&self, other: &Self) -> bool {
+		self.kind == other.kind
+	}
+}
+
+impl From<StatementKind> for Statement {
+	fn from(kind: StatementKind) -> Self {
+		Self::new(kind, Span::default())
 	}
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementKind {
+	Let { name: String, value: Expression },
+	Return { value: Expression },
+	Break,
+	Continue,
+	Throw { value: Expression },
+	Expression(Expression),
+	Block(Vec<Statement>),
+}
+
 impl std::fmt::Display for Statement {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.kind)
+	}
+}
+
+impl std::fmt::Display for StatementKind {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
-			Statement::Let { name, value } => write!(f, "let {name} = {value};"),
-			Statement::Return { value } => write!(f, "return {value};"),
-			Statement::Expression(expression) => write!(f, "{expression}"),
-			Statement::Block(statements) => {
+			StatementKind::Let { name, value } => write!(f, "let {name} = {value};"),
+			StatementKind::Return { value } => write!(f, "return {value};"),
+			StatementKind::Break => write!(f, "break;"),
+			StatementKind::Continue => write!(f, "continue;"),
+			StatementKind::Throw { value } => write!(f, "throw {value};"),
+			StatementKind::Expression(expression) => write!(f, "{expression}"),
+			StatementKind::Block(statements) => {
 				for statement in statements {
 					write!(f, "{statement}")?;
 				}
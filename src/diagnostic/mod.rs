@@ -0,0 +1,338 @@
+//! Rustc-style rendering of errors, pointing at the offending source code.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{parser::ParseError, RuntimeError, RuntimeErrorKind, SourceId, Span, TokenizeError};
+
+/// An error message attached to a location in the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+	pub message: String,
+	pub span: Span,
+	pub label: Option<String>,
+	pub notes: Vec<String>,
+	pub help: Vec<String>,
+
+	/// The calls that led to the error, innermost first.
+	pub backtrace: Vec<Call>,
+}
+
+/// An entry in the backtrace of a [`Diagnostic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+	pub description: String,
+
+	/// Where the call was made, if this entry stands for a single call.
+	pub span: Option<Span>,
+}
+
+/// The source code that spans can point into, e.g. every input to a REPL session. Errors can come
+/// from code that was read long before, like a function defined in an earlier input.
+#[derive(Debug, Default, Clone)]
+pub struct Sources {
+	/// The origin, usually a file name, and the code of every source, indexed by [`SourceId`].
+	sources: Vec<(String, String)>,
+}
+
+impl Sources {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Remembers `source`, returning the id to tokenize it with, see
+	/// [`crate::Tokenizer::with_source`].
+	pub fn add(&mut self, origin: impl Into<String>, source: impl Into<String>) -> SourceId {
+		self.sources.push((origin.into(), source.into()));
+		SourceId(self.sources.len() - 1)
+	}
+
+	/// The origin and the code of the source with the given id.
+	pub fn get(&self, id: SourceId) -> Option<(&str, &str)> {
+		self.sources
+			.get(id.0)
+			.map(|(origin, source)| (origin.as_str(), source.as_str()))
+	}
+}
+
+impl Diagnostic {
+	pub fn error(message: impl Into<String>, span: Span) -> Self {
+		Self {
+			message: message.into(),
+			span,
+			label: None,
+			notes: Vec::new(),
+			help: Vec::new(),
+			backtrace: Vec::new(),
+		}
+	}
+
+	pub fn with_label(mut self, label: impl Into<String>) -> Self {
+		self.label = Some(label.into());
+		self
+	}
+
+	pub fn with_note(mut self, note: impl Into<String>) -> Self {
+		self.notes.push(note.into());
+		self
+	}
+
+	pub fn with_help(mut self, help: impl Into<String>) -> Self {
+		self.help.push(help.into());
+		self
+	}
+
+	pub fn with_frame(mut self, description: impl Into<String>, span: Option<Span>) -> Self {
+		self.backtrace
+			.push(Call { description: description.into(), span });
+		self
+	}
+
+	/// Renders the diagnostic for `source`, which is the code that all spans point into.
+	///
+	/// `origin` is usually a file name and is displayed next to the location of the error.
+	pub fn render(&self, origin: &str, source: &str, color: bool) -> String {
+		self.render_with(|_| Some((origin, source)), color)
+	}
+
+	/// Renders the diagnostic for spans that can point into any of `sources`.
+	pub fn render_sources(&self, sources: &Sources, color: bool) -> String {
+		self.render_with(|id| sources.get(id), color)
+	}
+
+	fn render_with<'a>(
+		&self,
+		sources: impl Fn(SourceId) -> Option<(&'a str, &'a str)>,
+		color: bool,
+	) -> String {
+		let style = Style { color };
+		let Span { start, end, source } = self.span;
+		let known = sources(source);
+		let (origin, source) = known.unwrap_or(("<unknown>", ""));
+		let lines = source.lines().collect::<Vec<_>>();
+
+		// A span that ends right after a newline does not really cover the next line.
+		let last = if end.line > start.line && end.column == 1 { end.line - 1 } else { end.line };
+		let last = last.max(start.line);
+
+		let width = last.to_string().len();
+		let pad = " ".repeat(width);
+		let gutter = style.paint(BLUE, "|");
+		let mut out = String::new();
+
+		out += &format!("{}: {}\n", style.paint(RED, "error"), style.paint(BOLD, &self.message));
+		out += &format!("{pad}{} {origin}:{start}\n", style.paint(BLUE, "-->"));
+		out += &format!("{pad} {gutter}\n");
+
+		// Without the source there is nothing to point at.
+		for line in (start.line..=last).filter(|_| known.is_some()) {
+			let text = lines.get(line - 1).copied().unwrap_or_default();
+			let len = text.chars().count();
+
+			let from = if line == start.line { start.column } else { 1 };
+			let to = if line == end.line { end.column } else { len + 1 };
+			let to = to.max(from + 1);
+
+			let prefix = display_width(text.chars().take(from - 1));
+			let carets = display_width(text.chars().skip(from - 1).take(to - from)).max(1);
+			let mut underline = format!("{}{}", " ".repeat(prefix), "^".repeat(carets));
+
+			if let (true, Some(label)) = (line == last, &self.label) {
+				underline += &format!(" {label}");
+			}
+
+			let number = style.paint(BLUE, &format!("{line:>width$}"));
+			out += &format!("{number} {gutter} {}\n", expand_tabs(text));
+			out += &format!("{pad} {gutter} {}\n", style.paint(RED, &underline));
+		}
+
+		for note in &self.notes {
+			out += &format!(
+				"{pad} {} {}: {note}\n",
+				style.paint(BLUE, "="),
+				style.paint(BOLD, "note")
+			);
+		}
+
+		for help in &self.help {
+			out += &format!(
+				"{pad} {} {}: {help}\n",
+				style.paint(BLUE, "="),
+				style.paint(BOLD, "help")
+			);
+		}
+
+		if !self.backtrace.is_empty() {
+			out += &format!("{}\n", style.paint(BOLD, "backtrace (most recent call first):"));
+		}
+
+		for Call { description, span } in &self.backtrace {
+			match span {
+				Some(span) => {
+					let origin = sources(span.source).map_or("<unknown>", |(origin, _)| origin);
+					out += &format!("  {description}, called at {origin}:{}\n", span.start);
+				}
+				None => out += &format!("  {description}\n"),
+			}
+		}
+
+		out.truncate(out.trim_end().len());
+		out
+	}
+}
+
+impl From<&TokenizeError> for Diagnostic {
+	fn from(error: &TokenizeError) -> Self {
+		let diagnostic = Diagnostic::error(error.to_string(), error.span());
+
+		match error {
+			TokenizeError::UnterminatedString { .. } => diagnostic
+				.with_label("this string is never closed")
+				.with_help("add a `\"` at the end of the string"),
+		}
+	}
+}
+
+impl From<&ParseError> for Diagnostic {
+	fn from(error: &ParseError) -> Self {
+		let diagnostic = Diagnostic::error(error.to_string(), error.span());
+
+		match error {
+			ParseError::Tokenize(error) => error.into(),
+			ParseError::UnexpectedToken { got, expected } => diagnostic
+				.with_label(format!("unexpected `{}`", got.kind))
+				.with_note(format!("expected {}", expected.join(" or "))),
+			ParseError::NoPrefixParser { .. } => diagnostic.with_label("expected an expression"),
+			ParseError::InvalidPrefixOperator { .. } => {
+				diagnostic.with_label("expected `!` or `-`")
+			}
+			ParseError::InvalidCallee { .. } => diagnostic
+				.with_label("this cannot be called")
+				.with_help("only identifiers and function literals can be called"),
+			ParseError::InvalidAssignmentTarget { .. } => diagnostic
+				.with_label("this cannot be assigned to")
+				.with_help("only variables and indexed values like `a[0]` can be assigned to"),
+			ParseError::LoopControlOutsideLoop { .. } => {
+				diagnostic.with_label("not inside of a `while` or `for` loop")
+			}
+			ParseError::ReturnOutsideFunction { .. } => {
+				diagnostic.with_label("not inside of a function body")
+			}
+		}
+	}
+}
+
+impl From<&RuntimeError> for Diagnostic {
+	fn from(error: &RuntimeError) -> Self {
+		let diagnostic = Diagnostic::error(error.to_string(), error.span());
+
+		let mut diagnostic = match &*error.kind {
+			RuntimeErrorKind::BuiltinOverride { .. } => diagnostic
+				.with_label("this is a builtin function")
+				.with_help("use `let` to define a variable of the same name instead"),
+			RuntimeErrorKind::UnknownIdentifier { .. } => {
+				diagnostic.with_label("not found in this scope")
+			}
+			RuntimeErrorKind::InvalidMapKey { key, .. } => {
+				diagnostic.with_label(format!("this is of type `{key}`"))
+			}
+			RuntimeErrorKind::InvalidCondition { condition, .. } => {
+				diagnostic.with_label(format!("this is of type `{condition}`"))
+			}
+			RuntimeErrorKind::NotCallable { value, .. } => {
+				diagnostic.with_label(format!("`{value}` is not a function"))
+			}
+			RuntimeErrorKind::ArityMismatch { expected, .. } => {
+				diagnostic.with_label(format!("expected {expected} argument(s)"))
+			}
+			RuntimeErrorKind::InvalidArgument { position, got, .. } => {
+				diagnostic.with_label(format!("argument {position} is of type `{got}`"))
+			}
+			RuntimeErrorKind::InvalidPrefixOperand { operand, .. } => {
+				diagnostic.with_label(format!("operand is of type `{operand}`"))
+			}
+			RuntimeErrorKind::TypeMismatch { lhs, rhs, .. } => {
+				diagnostic.with_label(format!("`{lhs}` and `{rhs}` are incompatible"))
+			}
+			RuntimeErrorKind::NegativeRepetition { count, .. } => {
+				diagnostic.with_label(format!("`{count}` is negative"))
+			}
+			RuntimeErrorKind::InvalidIndex { lhs, index, .. } => {
+				diagnostic.with_label(format!("cannot index `{lhs}` with `{index}`"))
+			}
+			RuntimeErrorKind::NotIterable { value, .. } => diagnostic
+				.with_label(format!("this is of type `{value}`"))
+				.with_help("only arrays, maps, strings and ranges can be iterated over"),
+			RuntimeErrorKind::InvalidRange { .. } => {
+				diagnostic.with_label("both bounds need to be integers")
+			}
+			RuntimeErrorKind::IndexOutOfBounds { .. } => {
+				diagnostic.with_label("this element does not exist")
+			}
+			RuntimeErrorKind::DivisionByZero { .. } => diagnostic.with_label("the divisor is zero"),
+			RuntimeErrorKind::ReturnOutsideFunction { .. } => {
+				diagnostic.with_label("not inside of a function body")
+			}
+			RuntimeErrorKind::LoopControlOutsideLoop { .. } => {
+				diagnostic.with_label("not inside of a `while` or `for` loop")
+			}
+			RuntimeErrorKind::Thrown { .. } => diagnostic
+				.with_label("this was never caught")
+				.with_help("wrap the code in `try { ... } catch (e) { ... }` to handle the error"),
+			RuntimeErrorKind::Builtin { .. } => diagnostic.with_label("in this call"),
+			RuntimeErrorKind::ImmutableArgument { .. } => diagnostic
+				.with_label("this is not a variable")
+				.with_help("store the value in a variable first, e.g. `let a = ...;`"),
+			RuntimeErrorKind::StackOverflow { .. } => diagnostic
+				.with_label("this call is nested too deeply")
+				.with_help(
+					"make the recursive call the last thing the function does, so it replaces the current call instead of nesting",
+				),
+		};
+
+		// Recursion tends to repeat the same frame over and over, which we only show once.
+		let mut frames = error.backtrace.iter().rev().peekable();
+		while let Some(frame) = frames.next() {
+			diagnostic = diagnostic.with_frame(format!("in {frame}"), Some(frame.span));
+
+			let mut repeated = 0;
+			while frames.next_if_eq(&frame).is_some() {
+				repeated += 1;
+			}
+
+			if repeated > 0 {
+				diagnostic =
+					diagnostic.with_frame(format!("... repeated {repeated} more time(s)"), None);
+			}
+		}
+
+		diagnostic
+	}
+}
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy)]
+struct Style {
+	color: bool,
+}
+
+impl Style {
+	fn paint(&self, code: &str, text: &str) -> String {
+		if self.color { format!("{code}{text}{RESET}") } else { text.to_owned() }
+	}
+}
+
+/// How many columns it takes to print `chars`.
+fn display_width(chars: impl Iterator<Item = char>) -> usize {
+	chars
+		.map(|char| if char == '\t' { 4 } else { 1 })
+		.sum()
+}
+
+fn expand_tabs(text: &str) -> String {
+	text.replace('\t', "    ")
+}
@@ -0,0 +1,163 @@
+use {
+	crate::{
+		diagnostic::{Diagnostic, Sources},
+		eval::Environment,
+		Eval, Parser, Tokenizer,
+	},
+	color_eyre::Result,
+	pretty_assertions::assert_eq,
+	std::rc::Rc,
+};
+
+#[test]
+fn parse_error() -> Result<()> {
+	let input = "let x = 5;\nlet = 10;";
+	let tokenizer = Tokenizer::new(input.chars().collect());
+	let mut parser = Parser::new(tokenizer)?;
+	parser.parse_program();
+
+	let rendered = Diagnostic::from(&parser.errors[0]).render("test.mk", input, false);
+	let expected = [
+		"error: Unexpected token. Got: Assign, Wanted: identifier",
+		" --> test.mk:2:5",
+		"  |",
+		"2 | let = 10;",
+		"  |     ^ unexpected `=`",
+		"  = note: expected identifier",
+	]
+	.join("\n");
+
+	assert_eq!(rendered, expected);
+
+	Ok(())
+}
+
+#[test]
+fn runtime_error() -> Result<()> {
+	let input = "let add = fn(a, b) { a + b };\nadd(1, \"one\" - 1)";
+	let tokenizer = Tokenizer::new(input.chars().collect());
+	let mut parser = Parser::new(tokenizer)?;
+	let program = parser.parse_program();
+	let error = program
+		.eval(Rc::new(Environment::default()))
+		.unwrap_err();
+
+	let rendered = Diagnostic::from(&error).render("<repl>", input, false);
+	let expected = [
+		"error: Cannot perform subtraction on strings",
+		" --> <repl>:2:8",
+		"  |",
+		"2 | add(1, \"one\" - 1)",
+		"  |        ^^^^^^^^^ `string` and `int` are incompatible",
+	]
+	.join("\n");
+
+	assert_eq!(rendered, expected);
+
+	Ok(())
+}
+
+#[test]
+fn stack_overflow() -> Result<()> {
+	let input = "let f = fn(n) { 1 + f(n + 1) };\nf(0)";
+	let tokenizer = Tokenizer::new(input.chars().collect());
+	let mut parser = Parser::new(tokenizer)?;
+	let program = parser.parse_program();
+	let environment = Rc::new(Environment::default());
+	environment.call_stack().set_max_depth(10);
+	let error = program.eval(environment).unwrap_err();
+
+	let rendered = Diagnostic::from(&error).render("<repl>", input, false);
+	let expected = [
+		"error: Maximum recursion depth of 10 exceeded",
+		" --> <repl>:1:21",
+		"  |",
+		"1 | let f = fn(n) { 1 + f(n + 1) };",
+		"  |                     ^^^^^^^^ this call is nested too deeply",
+		"  = help: make the recursive call the last thing the function does, so it replaces the \
+		 current call instead of nesting",
+		"backtrace (most recent call first):",
+		"  in `f` with 1 argument, called at <repl>:1:21",
+		"  ... repeated 8 more time(s)",
+		"  in `f` with 1 argument, called at <repl>:2:1",
+	]
+	.join("\n");
+
+	assert_eq!(rendered, expected);
+
+	Ok(())
+}
+
+#[test]
+fn multiple_sources() -> Result<()> {
+	let environment = Rc::new(Environment::default());
+	let mut sources = Sources::new();
+	let mut parse = |origin, input: &str| -> Result<_> {
+		let id = sources.add(origin, input);
+		let tokenizer = Tokenizer::new(input.chars().collect()).with_source(id);
+		Ok(Parser::new(tokenizer)?.parse_program())
+	};
+
+	// The function fails long after the input it was defined in.
+	let definition = parse("lib.mk", "let f = fn(x) {\n  x / 0\n};")?;
+	let call = parse("<repl>", "f(5)")?;
+	definition.eval(Rc::clone(&environment))?;
+	let error = call.eval(environment).unwrap_err();
+
+	let rendered = Diagnostic::from(&error).render_sources(&sources, false);
+	let expected = [
+		"error: Division by zero in `5 / 0`",
+		" --> lib.mk:2:3",
+		"  |",
+		"2 |   x / 0",
+		"  |   ^^^^^ the divisor is zero",
+		"backtrace (most recent call first):",
+		"  in `f` with 1 argument, called at <repl>:1:1",
+	]
+	.join("\n");
+
+	assert_eq!(rendered, expected);
+
+	Ok(())
+}
+
+#[test]
+fn multiline_span() -> Result<()> {
+	let input = "let s = \"foo\n\tbar";
+	let tokenizer = Tokenizer::new(input.chars().collect());
+	let mut parser = Parser::new(tokenizer)?;
+	parser.parse_program();
+
+	let rendered = Diagnostic::from(&parser.errors[0]).render("<repl>", input, false);
+	let expected = [
+		"error: Unterminated string literal",
+		" --> <repl>:1:9",
+		"  |",
+		"1 | let s = \"foo",
+		"  |         ^^^^",
+		"2 |     bar",
+		"  | ^^^^^^^ this string is never closed",
+		"  = help: add a `\"` at the end of the string",
+	]
+	.join("\n");
+
+	assert_eq!(rendered, expected);
+
+	Ok(())
+}
+
+#[test]
+fn colors() {
+	let diagnostic = Diagnostic::error("oops", Default::default());
+
+	assert!(
+		diagnostic
+			.render("<repl>", "", true)
+			.contains("\x1b[")
+	);
+	assert!(
+		!diagnostic
+			.render("<repl>", "", false)
+			.contains("\x1b[")
+	);
+}
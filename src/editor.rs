@@ -0,0 +1,130 @@
+use {
+	color_eyre::{eyre::Context, Result},
+	monkey::eval::Environment,
+	rustyline::{
+		completion::Completer, highlight::Highlighter, hint::Hinter, history::DefaultHistory,
+		validate::Validator, Helper,
+	},
+	std::{path::PathBuf, rc::Rc},
+	tracing::warn,
+};
+
+const KEYWORDS: &[&str] = &[
+	"fn", "let", "if", "else", "return", "while", "for", "in", "break", "continue", "throw", "try",
+	"catch", "finally", "true", "false", "null",
+];
+
+/// Line editor used by the REPL, with persistent history and tab completion.
+pub struct Editor {
+	inner: rustyline::Editor<Completions, DefaultHistory>,
+	history: Option<PathBuf>,
+}
+
+impl Editor {
+	pub fn new(environment: Rc<Environment>) -> Result<Self> {
+		let mut inner = rustyline::Editor::new().context("Failed to initialize line editor")?;
+		inner.set_helper(Some(Completions { environment }));
+
+		let history = dirs::data_dir().map(|dir| dir.join("monkey").join("history"));
+
+		if let Some(path) = &history {
+			// The file won't exist the first time the REPL is started.
+			let _ = inner.load_history(path);
+		}
+
+		Ok(Self { inner, history })
+	}
+
+	pub fn read_line(&mut self, prompt: &str) -> rustyline::Result<String> {
+		self.inner.readline(prompt)
+	}
+
+	/// Adds `entry` to the history and persists it.
+	pub fn add_history(&mut self, entry: &str) {
+		let entry = entry.trim_end();
+
+		if entry.is_empty() {
+			return;
+		}
+
+		if let Err(error) = self.inner.add_history_entry(entry) {
+			warn!(?error, "Failed to add history entry");
+			return;
+		}
+
+		let Some(path) = &self.history else {
+			return;
+		};
+
+		if let Some(dir) = path.parent() {
+			if let Err(error) = std::fs::create_dir_all(dir) {
+				warn!(?error, "Failed to create history directory");
+				return;
+			}
+		}
+
+		if let Err(error) = self.inner.save_history(path) {
+			warn!(?error, "Failed to save history");
+		}
+	}
+
+	/// Sets the environment that is used to complete identifiers.
+	pub fn set_environment(&mut self, environment: Rc<Environment>) {
+		if let Some(helper) = self.inner.helper_mut() {
+			helper.environment = environment;
+		}
+	}
+}
+
+/// Completes keywords, builtins and any names bound in the REPL's environment.
+struct Completions {
+	environment: Rc<Environment>,
+}
+
+impl Completer for Completions {
+	type Candidate = String;
+
+	fn complete(
+		&self,
+		line: &str,
+		pos: usize,
+		_ctx: &rustyline::Context<'_>,
+	) -> rustyline::Result<(usize, Vec<String>)> {
+		let start = line[..pos]
+			.char_indices()
+			.rev()
+			.take_while(|(_, char)| char.is_alphanumeric() || *char == '_')
+			.last()
+			.map_or(pos, |(idx, _)| idx);
+
+		let word = &line[start..pos];
+		if word.is_empty() {
+			return Ok((pos, Vec::new()));
+		}
+
+		let mut candidates = KEYWORDS
+			.iter()
+			.map(|name| name.to_string())
+			.chain(self.environment.names())
+			.filter(|name| name.starts_with(word))
+			.collect::<Vec<_>>();
+
+		candidates.sort();
+		candidates.dedup();
+
+		Ok((start, candidates))
+	}
+}
+
+impl Hinter for Completions {
+	type Hint = String;
+}
+
+impl Highlighter for Completions {
+}
+
+impl Validator for Completions {
+}
+
+impl Helper for Completions {
+}
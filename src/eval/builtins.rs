@@ -1,17 +1,236 @@
 use {
-	crate::Value,
-	lazy_static::lazy_static,
-	std::{collections::HashSet, fmt::Debug},
+	super::{ControlFlow, RuntimeError, RuntimeErrorKind},
+	crate::{ast::InfixOperator, yeet, Expression, MapKey, Span, Value},
+	num_bigint::BigInt,
+	num_traits::FromPrimitive,
+	std::{cell::RefCell, cmp::Ordering, collections::HashMap, fmt::Debug, rc::Rc},
 };
 
-lazy_static! {
-	pub static ref BUILTINS: HashSet<&'static str> =
-		HashSet::from_iter(["print", "measure", "first"]);
+/// The builtin functions every interpreter starts out with.
+pub fn standard() -> Vec<Rc<dyn BuiltinFunction>> {
+	vec![
+		Rc::new(Print),
+		Rc::new(Measure),
+		Rc::new(First),
+		Rc::new(Int),
+		Rc::new(Error),
+		Rc::new(IsError),
+		Rc::new(Map),
+		Rc::new(Filter),
+		Rc::new(Reduce),
+		Rc::new(SortBy),
+		Rc::new(Each),
+		Rc::new(Any),
+		Rc::new(All),
+		Rc::new(Len),
+		Rc::new(Last),
+		Rc::new(Rest),
+		Rc::new(Push),
+		Rc::new(PushMut),
+		Rc::new(Pop),
+		Rc::new(Slice),
+		Rc::new(Concat),
+		Rc::new(Reverse),
+		Rc::new(Contains),
+		Rc::new(IndexOf),
+		Rc::new(Range),
+		Rc::new(Keys),
+		Rc::new(Values),
+		Rc::new(Entries),
+		Rc::new(Has),
+		Rc::new(Insert),
+		Rc::new(Remove),
+		Rc::new(Merge),
+		Rc::new(FromEntries),
+	]
 }
 
+/// How many arguments a function takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+	Fixed(usize),
+	/// Anywhere from `min` to `max` arguments, inclusive.
+	Range {
+		min: usize,
+		max: usize,
+	},
+	/// At least `min` arguments, with no upper limit.
+	Variadic {
+		min: usize,
+	},
+}
+
+impl Arity {
+	pub fn min(&self) -> usize {
+		match *self {
+			Arity::Fixed(count) => count,
+			Arity::Range { min, .. } | Arity::Variadic { min } => min,
+		}
+	}
+
+	pub fn max(&self) -> Option<usize> {
+		match *self {
+			Arity::Fixed(count) => Some(count),
+			Arity::Range { max, .. } => Some(max),
+			Arity::Variadic { .. } => None,
+		}
+	}
+
+	pub fn accepts(&self, count: usize) -> bool {
+		count >= self.min() && self.max().is_none_or(|max| count <= max)
+	}
+}
+
+impl std::fmt::Display for Arity {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Arity::Fixed(count) => write!(f, "{count}"),
+			Arity::Range { min, max } => write!(f, "{min} to {max}"),
+			Arity::Variadic { min } => write!(f, "at least {min}"),
+		}
+	}
+}
+
+/// The types a parameter accepts, named like in [`Value::type_name`]. Empty means anything goes.
+pub type Types = &'static [&'static str];
+
+/// Anything that can be called.
+pub const FUNCTION: Types = &["function", "builtin function"];
+
+/// The call a builtin function is running for, through which it can call other functions.
+#[derive(Debug)]
+pub struct Context {
+	span: Span,
+
+	/// The last value thrown by a function called through [`Context::call`]. A builtin only sees
+	/// the error, so we hold on to the value in case the builtin passes that error on.
+	thrown: RefCell<Option<(Value, RuntimeError)>>,
+}
+
+impl Context {
+	pub(super) fn new(span: Span) -> Self {
+		Self { span, thrown: RefCell::default() }
+	}
+
+	/// Where the builtin was called.
+	pub fn span(&self) -> Span {
+		self.span
+	}
+
+	/// An error that makes the builtin fail with `message`.
+	pub fn error(&self, message: impl Into<String>) -> RuntimeError {
+		RuntimeErrorKind::Builtin { message: message.into(), span: self.span }.into()
+	}
+
+	/// Calls `function`, which can be a Monkey function or another builtin, as if it was called
+	/// where the builtin was called.
+	pub fn call(&self, function: &Value, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		match Expression::call(function.clone(), arguments, self.span) {
+			Ok(value) => Ok(value),
+			Err(ControlFlow::Error(error)) => Err(error),
+			Err(ControlFlow::Throw { value, error }) => {
+				*self.thrown.borrow_mut() = Some((*value, error.clone()));
+				Err(error)
+			}
+			Err(flow) => unreachable!("calls never end with `{flow:?}`"),
+		}
+	}
+
+	/// Calls `function` and makes sure the result can be used as a condition.
+	pub fn test(&self, function: &Value, arguments: Vec<Value>) -> Result<bool, RuntimeError> {
+		let value = self.call(function, arguments)?;
+		match value.truthiness() {
+			Some(bool) => Ok(bool),
+			None => Err(RuntimeErrorKind::InvalidCondition {
+				condition: value.type_name(),
+				span: self.span,
+			}
+			.into()),
+		}
+	}
+
+	/// Turns an error returned by the builtin back into a throw, if that's what it came from.
+	pub(super) fn rethrow(self, error: RuntimeError) -> ControlFlow {
+		match self.thrown.into_inner() {
+			Some((value, thrown)) if thrown == error => {
+				ControlFlow::Throw { value: Box::new(value), error }
+			}
+			_ => ControlFlow::Error(error),
+		}
+	}
+}
+
+/// A function implemented in Rust.
+///
+/// The evaluator checks the arguments against [`BuiltinFunction::arity`] and
+/// [`BuiltinFunction::parameters`] before calling it, so misuse is reported the same way for
+/// every builtin. Things that can go wrong with valid arguments, like parsing a number, are
+/// reported by returning a [`Value::Error`] instead.
 pub trait BuiltinFunction: Debug {
 	fn name(&self) -> String;
-	fn call(&self, arguments: Vec<Value>) -> Value;
+
+	fn arity(&self) -> Arity;
+
+	/// The types each parameter accepts, in order. Arguments past the end are checked against the
+	/// last entry, so variadic functions only need one entry for all of their extra arguments.
+	fn parameters(&self) -> &'static [Types] {
+		&[]
+	}
+
+	/// Runs the function with arguments that were already checked.
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError>;
+
+	/// Whether the function changes its first argument in place, like `pop(array)`. Calls to it
+	/// then need a variable or an element of one as the first argument, like `a` or `a[0]`, and
+	/// [`BuiltinFunction::call_mut`] runs instead of [`BuiltinFunction::call`].
+	fn mutates(&self) -> bool {
+		false
+	}
+
+	/// Runs a function that [mutates](BuiltinFunction::mutates) its first argument, `target`. The
+	/// other arguments were checked together with `target`, like for [`BuiltinFunction::call`].
+	fn call_mut(
+		&self,
+		context: &Context,
+		target: &mut Value,
+		arguments: Vec<Value>,
+	) -> Result<Value, RuntimeError> {
+		let _ = (context, target, arguments);
+		unreachable!("`{}` doesn't change its arguments", self.name())
+	}
+}
+
+/// Makes sure `arguments` fit what `function` declares, before it runs.
+pub(super) fn check_arguments(
+	function: &dyn BuiltinFunction,
+	arguments: &[Value],
+	span: Span,
+) -> Result<(), RuntimeError> {
+	let expected = function.arity();
+	let got = arguments.len();
+	if !expected.accepts(got) {
+		yeet!(RuntimeErrorKind::ArityMismatch { expected, got, span });
+	}
+
+	let parameters = function.parameters();
+	for (index, argument) in arguments.iter().enumerate() {
+		let Some(&expected) = parameters.get(index).or(parameters.last()) else {
+			break;
+		};
+
+		let got = argument.type_name();
+		if !expected.is_empty() && !expected.contains(&got) {
+			yeet!(RuntimeErrorKind::InvalidArgument {
+				function: function.name(),
+				position: index + 1,
+				expected: expected.join(" or "),
+				got,
+				span,
+			});
+		}
+	}
+
+	Ok(())
 }
 
 #[derive(Debug)]
@@ -22,7 +241,11 @@ impl BuiltinFunction for Print {
 		String::from("print")
 	}
 
-	fn call(&self, arguments: Vec<Value>) -> Value {
+	fn arity(&self) -> Arity {
+		Arity::Variadic { min: 0 }
+	}
+
+	fn call(&self, _: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
 		let result = arguments
 			.into_iter()
 			.map(|arg| arg.to_string())
@@ -31,7 +254,7 @@ impl BuiltinFunction for Print {
 
 		println!("{result}");
 
-		Value::Null
+		Ok(Value::Null)
 	}
 }
 
@@ -43,26 +266,25 @@ impl BuiltinFunction for Measure {
 		String::from("measure")
 	}
 
-	fn call(&self, arguments: Vec<Value>) -> Value {
-		let is_empty = arguments.is_empty();
-		let is_invalid = arguments
-			.iter()
-			.any(|value| !matches!(value, Value::String(_) | Value::Array(_)));
+	fn arity(&self) -> Arity {
+		Arity::Variadic { min: 1 }
+	}
 
-		if is_empty || is_invalid {
-			return Value::Null;
-		}
+	fn parameters(&self) -> &'static [Types] {
+		&[&["string", "array"]]
+	}
 
+	fn call(&self, _: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
 		let mut lens = arguments
 			.into_iter()
 			.map(|value| match value {
 				Value::String(string) => Value::Int(string.len() as i64),
 				Value::Array(array) => Value::Int(array.len() as i64),
-				value => panic!("Expected string or array, but got `{value:?}`"),
+				value => unreachable!("`{value:?}` passed the argument check"),
 			})
 			.collect::<Vec<_>>();
 
-		if lens.len() > 1 { Value::Array(lens) } else { lens.remove(0) }
+		Ok(if lens.len() > 1 { Value::Array(lens) } else { lens.remove(0) })
 	}
 }
 
@@ -74,41 +296,1043 @@ impl BuiltinFunction for First {
 		String::from("first")
 	}
 
-	fn call(&self, mut arguments: Vec<Value>) -> Value {
-		match arguments.len() {
-			0 => Value::Null,
-			1 => {
-				let Value::Array(mut array) = arguments.remove(0) else {
-					return Value::Null;
-				};
+	fn arity(&self) -> Arity {
+		Arity::Variadic { min: 1 }
+	}
 
-				if array.is_empty() {
-					return Value::Null;
-				}
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"]]
+	}
+
+	fn call(&self, _: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		// An empty array has no first element, which is not an error.
+		let mut firsts = arguments
+			.into_iter()
+			.map(|array| match array {
+				Value::Array(array) => array.into_iter().next().unwrap_or(Value::Null),
+				value => unreachable!("`{value:?}` passed the argument check"),
+			})
+			.collect::<Vec<_>>();
+
+		Ok(if firsts.len() > 1 { Value::Array(firsts) } else { firsts.remove(0) })
+	}
+}
+
+#[derive(Debug)]
+pub struct Int;
+
+impl BuiltinFunction for Int {
+	fn name(&self) -> String {
+		String::from("int")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["int", "float", "bool", "string"]]
+	}
+
+	fn call(&self, _: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let value = arguments.remove(0);
+
+		Ok(match value {
+			Value::Bool(bool) => Value::Int(bool.into()),
+			// Rounds towards zero, like casting in most languages.
+			Value::Float(float) => match BigInt::from_f64(float.trunc()) {
+				Some(int) => Value::from(int),
+				None => Value::error(format!("cannot convert `{value}` to an int")),
+			},
+			Value::String(string) => match string.trim().parse::<BigInt>() {
+				Ok(int) => Value::from(int),
+				Err(_) => Value::error(format!("cannot parse `{string}` as an int")),
+			},
+			value => value,
+		})
+	}
+}
+
+/// Creates an error value from its argument, to be returned from functions that can fail.
+#[derive(Debug)]
+pub struct Error;
+
+impl BuiltinFunction for Error {
+	fn name(&self) -> String {
+		String::from("error")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn call(&self, _: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		Ok(Value::Error(Box::new(arguments.remove(0))))
+	}
+}
+
+#[derive(Debug)]
+pub struct IsError;
+
+impl BuiltinFunction for IsError {
+	fn name(&self) -> String {
+		String::from("is_error")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn call(&self, _: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		Ok(Value::Bool(matches!(arguments[0], Value::Error(_))))
+	}
+}
+
+/// Splits the arguments of a builtin that takes an array and a function.
+fn array_and_function(arguments: Vec<Value>) -> (Vec<Value>, Value) {
+	match <[Value; 2]>::try_from(arguments) {
+		Ok([Value::Array(array), function]) => (array, function),
+		arguments => unreachable!("`{arguments:?}` passed the argument check"),
+	}
+}
+
+/// `map(array, f)` calls `f` on every element and collects the results into a new array.
+#[derive(Debug)]
+pub struct Map;
+
+impl BuiltinFunction for Map {
+	fn name(&self) -> String {
+		String::from("map")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], FUNCTION]
+	}
+
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let (array, function) = array_and_function(arguments);
+		let array = array
+			.into_iter()
+			.map(|element| context.call(&function, vec![element]))
+			.collect::<Result<_, _>>()?;
+
+		Ok(Value::Array(array))
+	}
+}
+
+/// `filter(array, f)` keeps the elements that `f` returns a truthy value for.
+#[derive(Debug)]
+pub struct Filter;
 
-				array.remove(0)
+impl BuiltinFunction for Filter {
+	fn name(&self) -> String {
+		String::from("filter")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], FUNCTION]
+	}
+
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let (array, function) = array_and_function(arguments);
+		let mut kept = Vec::new();
+
+		for element in array {
+			if context.test(&function, vec![element.clone()])? {
+				kept.push(element);
 			}
-			_ => {
-				if arguments
-					.iter()
-					.any(|value| !matches!(value, Value::Array(_)))
-				{
-					return Value::Null;
+		}
+
+		Ok(Value::Array(kept))
+	}
+}
+
+/// `reduce(array, initial, f)` folds the array into a single value, starting with `initial` and
+/// replacing it with `f(accumulator, element)` for every element.
+#[derive(Debug)]
+pub struct Reduce;
+
+impl BuiltinFunction for Reduce {
+	fn name(&self) -> String {
+		String::from("reduce")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(3)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], &[], FUNCTION]
+	}
+
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let Ok([Value::Array(array), initial, function]) = <[Value; 3]>::try_from(arguments) else {
+			unreachable!("the arguments passed the argument check");
+		};
+
+		array
+			.into_iter()
+			.try_fold(initial, |accumulator, element| {
+				context.call(&function, vec![accumulator, element])
+			})
+	}
+}
+
+/// `sort_by(array, f)` returns a sorted copy of the array, ordered by what `f` returns for each
+/// element. Elements with equal keys keep their order.
+#[derive(Debug)]
+pub struct SortBy;
+
+impl BuiltinFunction for SortBy {
+	fn name(&self) -> String {
+		String::from("sort_by")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], FUNCTION]
+	}
+
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let (array, function) = array_and_function(arguments);
+		let mut keyed = array
+			.into_iter()
+			.map(|element| Ok((context.call(&function, vec![element.clone()])?, element)))
+			.collect::<Result<Vec<_>, RuntimeError>>()?;
+
+		// Keys are compared like with `<`, which can fail, so we remember the first failure and
+		// treat the keys as equal until the sort is done.
+		let mut error = None;
+		let mut less_than = |lhs: &Value, rhs: &Value| {
+			let less = Expression::eval_infix(
+				InfixOperator::LessThan,
+				lhs.clone(),
+				rhs.clone(),
+				context.span(),
+			);
+
+			match less {
+				Ok(Value::Bool(bool)) => bool,
+				Ok(_) => false,
+				Err(failure) => {
+					error.get_or_insert(failure);
+					false
 				}
+			}
+		};
+
+		keyed.sort_by(|(lhs, _), (rhs, _)| {
+			if less_than(lhs, rhs) {
+				Ordering::Less
+			} else if less_than(rhs, lhs) {
+				Ordering::Greater
+			} else {
+				Ordering::Equal
+			}
+		});
 
-				let values = arguments
+		match error {
+			Some(error) => Err(error),
+			None => Ok(Value::Array(
+				keyed
 					.into_iter()
-					.map(|array| {
-						let Value::Array(mut array) = array else {
-							panic!("Expected array");
-						};
+					.map(|(_, element)| element)
+					.collect(),
+			)),
+		}
+	}
+}
+
+/// `each(array, f)` calls `f` on every element, for its side effects.
+#[derive(Debug)]
+pub struct Each;
+
+impl BuiltinFunction for Each {
+	fn name(&self) -> String {
+		String::from("each")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], FUNCTION]
+	}
+
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let (array, function) = array_and_function(arguments);
+		for element in array {
+			context.call(&function, vec![element])?;
+		}
+
+		Ok(Value::Null)
+	}
+}
+
+/// `any(array, f)` checks whether `f` returns a truthy value for at least one element. It stops
+/// at the first one that does.
+#[derive(Debug)]
+pub struct Any;
+
+impl BuiltinFunction for Any {
+	fn name(&self) -> String {
+		String::from("any")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], FUNCTION]
+	}
+
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let (array, function) = array_and_function(arguments);
+		for element in array {
+			if context.test(&function, vec![element])? {
+				return Ok(Value::Bool(true));
+			}
+		}
+
+		Ok(Value::Bool(false))
+	}
+}
+
+/// `all(array, f)` checks whether `f` returns a truthy value for every element. It stops at the
+/// first one that doesn't.
+#[derive(Debug)]
+pub struct All;
+
+impl BuiltinFunction for All {
+	fn name(&self) -> String {
+		String::from("all")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], FUNCTION]
+	}
+
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let (array, function) = array_and_function(arguments);
+		for element in array {
+			if !context.test(&function, vec![element])? {
+				return Ok(Value::Bool(false));
+			}
+		}
+
+		Ok(Value::Bool(true))
+	}
+}
+
+/// Takes the array out of an argument that passed the argument check.
+fn into_array(value: Value) -> Vec<Value> {
+	match value {
+		Value::Array(array) => array,
+		value => unreachable!("`{value:?}` passed the argument check"),
+	}
+}
+
+/// Gets the number out of an argument that passed the argument check, failing if it doesn't fit
+/// into an `i64`.
+fn into_int(value: Value, context: &Context) -> Result<i64, RuntimeError> {
+	match value {
+		Value::Int(int) => Ok(int),
+		Value::BigInt(int) => Err(context.error(format!("`{int}` is too large"))),
+		value => unreachable!("`{value:?}` passed the argument check"),
+	}
+}
+
+/// `len(value)` is the number of elements in an array, map or range, or characters in a string.
+#[derive(Debug)]
+pub struct Len;
+
+impl BuiltinFunction for Len {
+	fn name(&self) -> String {
+		String::from("len")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array", "map", "string", "range"]]
+	}
+
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let len = match &arguments[0] {
+			Value::Array(array) => array.len() as i64,
+			Value::Map(map) => map.len() as i64,
+			Value::String(string) => string.chars().count() as i64,
+			range @ &Value::Range { start, end, inclusive } => {
+				// Wide enough for any two `i64`s, so only the result itself can overflow.
+				let len = (end as i128 - start as i128 + inclusive as i128).max(0);
+				i64::try_from(len).map_err(|_| {
+					context.error(format!("the length of `{range}` does not fit into an int"))
+				})?
+			}
+			value => unreachable!("`{value:?}` passed the argument check"),
+		};
+
+		Ok(Value::Int(len))
+	}
+}
+
+/// `last(array)` is the last element, or `null` if the array is empty.
+#[derive(Debug)]
+pub struct Last;
+
+impl BuiltinFunction for Last {
+	fn name(&self) -> String {
+		String::from("last")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"]]
+	}
+
+	fn call(&self, _: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let array = into_array(arguments.remove(0));
+		Ok(array.into_iter().last().unwrap_or(Value::Null))
+	}
+}
+
+/// `rest(array)` is everything but the first element, or `null` if the array is empty.
+#[derive(Debug)]
+pub struct Rest;
+
+impl BuiltinFunction for Rest {
+	fn name(&self) -> String {
+		String::from("rest")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"]]
+	}
 
-						if array.is_empty() { Value::Null } else { array.remove(0) }
-					})
-					.collect::<Vec<_>>();
+	fn call(&self, _: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let mut array = into_array(arguments.remove(0));
+		if array.is_empty() {
+			return Ok(Value::Null);
+		}
+
+		array.remove(0);
+		Ok(Value::Array(array))
+	}
+}
+
+/// `push(array, value)` is a copy of the array with `value` added to the end. See [`PushMut`] for
+/// changing the array itself.
+#[derive(Debug)]
+pub struct Push;
+
+impl BuiltinFunction for Push {
+	fn name(&self) -> String {
+		String::from("push")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], &[]]
+	}
+
+	fn call(&self, _: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let value = arguments.pop().expect("the arity was checked");
+		let mut array = into_array(arguments.remove(0));
+		array.push(value);
+
+		Ok(Value::Array(array))
+	}
+}
+
+/// `push_mut(array, value)` adds `value` to the end of the array stored in a variable, and returns
+/// the new length.
+#[derive(Debug)]
+pub struct PushMut;
+
+impl BuiltinFunction for PushMut {
+	fn name(&self) -> String {
+		String::from("push_mut")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], &[]]
+	}
+
+	/// Only reached through other builtins, which can't change the array they were given.
+	fn call(&self, context: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let mut target = arguments.remove(0);
+		self.call_mut(context, &mut target, arguments)
+	}
+
+	fn mutates(&self) -> bool {
+		true
+	}
+
+	fn call_mut(
+		&self,
+		_: &Context,
+		target: &mut Value,
+		mut arguments: Vec<Value>,
+	) -> Result<Value, RuntimeError> {
+		let Value::Array(array) = target else {
+			unreachable!("`{target:?}` passed the argument check");
+		};
+
+		array.push(arguments.remove(0));
+		Ok(Value::Int(array.len() as i64))
+	}
+}
+
+/// `pop(array)` removes the last element from the array stored in a variable and returns it, or
+/// `null` if the array is empty.
+#[derive(Debug)]
+pub struct Pop;
+
+impl BuiltinFunction for Pop {
+	fn name(&self) -> String {
+		String::from("pop")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"]]
+	}
+
+	/// Only reached through other builtins, which can't change the array they were given.
+	fn call(&self, context: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let mut target = arguments.remove(0);
+		self.call_mut(context, &mut target, arguments)
+	}
+
+	fn mutates(&self) -> bool {
+		true
+	}
+
+	fn call_mut(
+		&self,
+		_: &Context,
+		target: &mut Value,
+		_: Vec<Value>,
+	) -> Result<Value, RuntimeError> {
+		let Value::Array(array) = target else {
+			unreachable!("`{target:?}` passed the argument check");
+		};
 
-				Value::Array(values)
+		Ok(array.pop().unwrap_or(Value::Null))
+	}
+}
+
+/// `slice(array, start, end)` copies the elements from `start` up to, but not including, `end`.
+/// Without `end`, it copies everything from `start` on. Negative positions count from the end.
+#[derive(Debug)]
+pub struct Slice;
+
+impl BuiltinFunction for Slice {
+	fn name(&self) -> String {
+		String::from("slice")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Range { min: 2, max: 3 }
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], &["int"]]
+	}
+
+	fn call(&self, context: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let array = into_array(arguments.remove(0));
+		let length = array.len();
+
+		// Unlike with indexing, the end of the array is a valid position.
+		let position = |value: Value| -> Result<usize, RuntimeError> {
+			let index = into_int(value, context)?;
+			let position = if index < 0 { index + length as i64 } else { index };
+
+			match usize::try_from(position) {
+				Ok(position) if position <= length => Ok(position),
+				_ => {
+					Err(RuntimeErrorKind::IndexOutOfBounds { index, length, span: context.span() }
+						.into())
+				}
 			}
+		};
+
+		let mut arguments = arguments.into_iter();
+		let start = position(arguments.next().expect("the arity was checked"))?;
+		let end = arguments.next().map_or(Ok(length), position)?;
+
+		if start > end {
+			return Err(context.error(format!("cannot slice from {start} to {end}")));
 		}
+
+		Ok(Value::Array(array[start..end].to_vec()))
+	}
+}
+
+/// `concat(arrays...)` joins all of the arrays into one.
+#[derive(Debug)]
+pub struct Concat;
+
+impl BuiltinFunction for Concat {
+	fn name(&self) -> String {
+		String::from("concat")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Variadic { min: 1 }
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"]]
+	}
+
+	fn call(&self, _: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		Ok(Value::Array(
+			arguments
+				.into_iter()
+				.flat_map(into_array)
+				.collect(),
+		))
+	}
+}
+
+/// `reverse(array)` is a copy of the array in reverse order.
+#[derive(Debug)]
+pub struct Reverse;
+
+impl BuiltinFunction for Reverse {
+	fn name(&self) -> String {
+		String::from("reverse")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"]]
+	}
+
+	fn call(&self, _: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let mut array = into_array(arguments.remove(0));
+		array.reverse();
+
+		Ok(Value::Array(array))
+	}
+}
+
+/// `contains(array, value)` checks whether any element is equal to `value`.
+#[derive(Debug)]
+pub struct Contains;
+
+impl BuiltinFunction for Contains {
+	fn name(&self) -> String {
+		String::from("contains")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], &[]]
+	}
+
+	fn call(&self, _: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let Value::Array(array) = &arguments[0] else {
+			unreachable!("`{:?}` passed the argument check", arguments[0]);
+		};
+
+		Ok(Value::Bool(array.contains(&arguments[1])))
+	}
+}
+
+/// `index_of(array, value)` is the position of the first element equal to `value`, or `null` if
+/// there is none.
+#[derive(Debug)]
+pub struct IndexOf;
+
+impl BuiltinFunction for IndexOf {
+	fn name(&self) -> String {
+		String::from("index_of")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"], &[]]
+	}
+
+	fn call(&self, _: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let Value::Array(array) = &arguments[0] else {
+			unreachable!("`{:?}` passed the argument check", arguments[0]);
+		};
+
+		Ok(array
+			.iter()
+			.position(|element| *element == arguments[1])
+			.map_or(Value::Null, |position| Value::Int(position as i64)))
+	}
+}
+
+/// `range(end)`, `range(start, end)` or `range(start, end, step)` creates an array of the numbers
+/// from `start` (or 0) up to, but not including, `end`, counting in steps of `step` (or 1).
+#[derive(Debug)]
+pub struct Range;
+
+/// How many numbers `range` creates at most, so a huge range raises an error instead of exhausting
+/// the host's memory. `start..end` expressions are lazy and aren't limited.
+const MAX_RANGE_LEN: i128 = 1 << 24;
+
+impl BuiltinFunction for Range {
+	fn name(&self) -> String {
+		String::from("range")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Range { min: 1, max: 3 }
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["int"]]
+	}
+
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let numbers = arguments
+			.into_iter()
+			.map(|argument| into_int(argument, context))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let (start, end, step) = match numbers[..] {
+			[end] => (0, end, 1),
+			[start, end] => (start, end, 1),
+			[start, end, step] => (start, end, step),
+			_ => unreachable!("the arity was checked"),
+		};
+
+		if step == 0 {
+			return Err(context.error("the step of `range` cannot be zero"));
+		}
+
+		// Wide enough for any three `i64`s, and every number in the range fits into an `i64` again.
+		let (start, end, step) = (start as i128, end as i128, step as i128);
+		let len = ((end - start + step - step.signum()) / step).max(0);
+		if len > MAX_RANGE_LEN {
+			return Err(context.error(format!(
+				"`range` can create at most {MAX_RANGE_LEN} numbers, but this would be {len}"
+			)));
+		}
+
+		let numbers = (0..len)
+			.map(|n| Value::Int((start + n * step) as i64))
+			.collect();
+
+		Ok(Value::Array(numbers))
+	}
+}
+
+/// Takes the map out of an argument that passed the argument check.
+fn into_map(value: Value) -> HashMap<MapKey, Value> {
+	match value {
+		Value::Map(map) => map,
+		value => unreachable!("`{value:?}` passed the argument check"),
+	}
+}
+
+/// `keys(map)` is an array of the keys of the map, in order.
+#[derive(Debug)]
+pub struct Keys;
+
+impl BuiltinFunction for Keys {
+	fn name(&self) -> String {
+		String::from("keys")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["map"]]
+	}
+
+	fn call(&self, _: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let entries = Expression::sorted_entries(into_map(arguments.remove(0)));
+		Ok(Value::Array(
+			entries
+				.into_iter()
+				.map(|(key, _)| Value::from(key))
+				.collect(),
+		))
+	}
+}
+
+/// `values(map)` is an array of the values of the map, in the order of their keys.
+#[derive(Debug)]
+pub struct Values;
+
+impl BuiltinFunction for Values {
+	fn name(&self) -> String {
+		String::from("values")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["map"]]
+	}
+
+	fn call(&self, _: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let entries = Expression::sorted_entries(into_map(arguments.remove(0)));
+		Ok(Value::Array(
+			entries
+				.into_iter()
+				.map(|(_, value)| value)
+				.collect(),
+		))
+	}
+}
+
+/// `entries(map)` is an array of `[key, value]` pairs, in the order of their keys.
+#[derive(Debug)]
+pub struct Entries;
+
+impl BuiltinFunction for Entries {
+	fn name(&self) -> String {
+		String::from("entries")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["map"]]
+	}
+
+	fn call(&self, _: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let entries = Expression::sorted_entries(into_map(arguments.remove(0)));
+		Ok(Value::Array(
+			entries
+				.into_iter()
+				.map(|(key, value)| Value::Array(vec![Value::from(key), value]))
+				.collect(),
+		))
+	}
+}
+
+/// `has(map, key)` checks whether the map contains `key`.
+#[derive(Debug)]
+pub struct Has;
+
+impl BuiltinFunction for Has {
+	fn name(&self) -> String {
+		String::from("has")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["map"], &[]]
+	}
+
+	fn call(&self, context: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let key =
+			Expression::eval_key(arguments.pop().expect("the arity was checked"), context.span())?;
+		Ok(Value::Bool(into_map(arguments.remove(0)).contains_key(&key)))
+	}
+}
+
+/// `insert(map, key, value)` sets `key` to `value` in the map stored in a variable, and returns
+/// the value it replaced, or `null` if there was none.
+#[derive(Debug)]
+pub struct Insert;
+
+impl BuiltinFunction for Insert {
+	fn name(&self) -> String {
+		String::from("insert")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(3)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["map"], &[]]
+	}
+
+	/// Only reached through other builtins, which can't change the map they were given.
+	fn call(&self, context: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let mut target = arguments.remove(0);
+		self.call_mut(context, &mut target, arguments)
+	}
+
+	fn mutates(&self) -> bool {
+		true
+	}
+
+	fn call_mut(
+		&self,
+		context: &Context,
+		target: &mut Value,
+		arguments: Vec<Value>,
+	) -> Result<Value, RuntimeError> {
+		let Value::Map(map) = target else {
+			unreachable!("`{target:?}` passed the argument check");
+		};
+
+		let [key, value] = <[Value; 2]>::try_from(arguments).expect("the arity was checked");
+		let key = Expression::eval_key(key, context.span())?;
+
+		Ok(map.insert(key, value).unwrap_or(Value::Null))
+	}
+}
+
+/// `remove(map, key)` removes `key` from the map stored in a variable, and returns the value it
+/// had, or `null` if there was none.
+#[derive(Debug)]
+pub struct Remove;
+
+impl BuiltinFunction for Remove {
+	fn name(&self) -> String {
+		String::from("remove")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(2)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["map"], &[]]
+	}
+
+	/// Only reached through other builtins, which can't change the map they were given.
+	fn call(&self, context: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let mut target = arguments.remove(0);
+		self.call_mut(context, &mut target, arguments)
+	}
+
+	fn mutates(&self) -> bool {
+		true
+	}
+
+	fn call_mut(
+		&self,
+		context: &Context,
+		target: &mut Value,
+		mut arguments: Vec<Value>,
+	) -> Result<Value, RuntimeError> {
+		let Value::Map(map) = target else {
+			unreachable!("`{target:?}` passed the argument check");
+		};
+
+		let key = Expression::eval_key(arguments.remove(0), context.span())?;
+		Ok(map.remove(&key).unwrap_or(Value::Null))
+	}
+}
+
+/// `merge(maps...)` combines all of the maps into one. Later maps win when a key appears more
+/// than once.
+#[derive(Debug)]
+pub struct Merge;
+
+impl BuiltinFunction for Merge {
+	fn name(&self) -> String {
+		String::from("merge")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Variadic { min: 1 }
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["map"]]
+	}
+
+	fn call(&self, _: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		Ok(Value::Map(arguments.into_iter().flat_map(into_map).collect()))
+	}
+}
+
+/// `from_entries(array)` creates a map from an array of `[key, value]` pairs, the opposite of
+/// [`Entries`]. Later pairs win when a key appears more than once.
+#[derive(Debug)]
+pub struct FromEntries;
+
+impl BuiltinFunction for FromEntries {
+	fn name(&self) -> String {
+		String::from("from_entries")
+	}
+
+	fn arity(&self) -> Arity {
+		Arity::Fixed(1)
+	}
+
+	fn parameters(&self) -> &'static [Types] {
+		&[&["array"]]
+	}
+
+	fn call(&self, context: &Context, mut arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let mut map = HashMap::new();
+
+		for (index, entry) in into_array(arguments.remove(0))
+			.into_iter()
+			.enumerate()
+		{
+			let [key, value] = match entry {
+				Value::Array(pair) => <[Value; 2]>::try_from(pair).map_err(Value::Array),
+				entry => Err(entry),
+			}
+			.map_err(|entry| {
+				context.error(format!("element {index} is not a `[key, value]` pair: {entry}"))
+			})?;
+
+			map.insert(Expression::eval_key(key, context.span())?, value);
+		}
+
+		Ok(Value::Map(map))
 	}
 }
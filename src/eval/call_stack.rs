@@ -0,0 +1,93 @@
+use {
+	crate::{yeet, RuntimeError, RuntimeErrorKind, Span},
+	std::cell::{Cell, RefCell},
+};
+
+/// How deep calls can be nested by default. Deeper recursion would overflow the native stack.
+pub const DEFAULT_MAX_DEPTH: usize = 1000;
+
+/// A call to a Monkey function that hasn't returned yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+	/// The name of the function, if it was bound with `let`.
+	pub name: Option<String>,
+
+	/// Where the function was called.
+	pub span: Span,
+
+	/// How many arguments the function was called with.
+	pub arguments: usize,
+}
+
+impl std::fmt::Display for Frame {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match &self.name {
+			Some(name) => write!(f, "`{name}`")?,
+			None => write!(f, "anonymous function")?,
+		}
+
+		let plural = if self.arguments == 1 { "" } else { "s" };
+		write!(f, " with {} argument{plural}", self.arguments)
+	}
+}
+
+/// The Monkey functions that are currently running, shared by all environments of an
+/// interpreter.
+pub struct CallStack {
+	frames: RefCell<Vec<Frame>>,
+	max_depth: Cell<usize>,
+}
+
+impl CallStack {
+	pub fn max_depth(&self) -> usize {
+		self.max_depth.get()
+	}
+
+	pub fn set_max_depth(&self, max_depth: usize) {
+		self.max_depth.set(max_depth);
+	}
+
+	/// The calls that are currently running, innermost last.
+	pub fn frames(&self) -> Vec<Frame> {
+		self.frames.borrow().clone()
+	}
+
+	/// Enters a call, failing if that would nest calls deeper than allowed.
+	pub(super) fn push(&self, frame: Frame) -> Result<(), RuntimeError> {
+		let limit = self.max_depth();
+		if self.frames.borrow().len() >= limit {
+			yeet!(RuntimeErrorKind::StackOverflow { limit, span: frame.span });
+		}
+
+		self.frames.borrow_mut().push(frame);
+		Ok(())
+	}
+
+	/// Swaps out the innermost call for a tail call that takes its place.
+	pub(super) fn replace(&self, frame: Frame) {
+		if let Some(last) = self.frames.borrow_mut().last_mut() {
+			*last = frame;
+		}
+	}
+
+	/// Leaves the innermost call.
+	pub(super) fn pop(&self) {
+		self.frames.borrow_mut().pop();
+	}
+}
+
+impl Default for CallStack {
+	fn default() -> Self {
+		Self { frames: RefCell::default(), max_depth: Cell::new(DEFAULT_MAX_DEPTH) }
+	}
+}
+
+impl std::fmt::Debug for CallStack {
+	/// Only prints the depth, since the frames themselves get long quickly.
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("CallStack")
+			.field("depth", &self.frames.borrow().len())
+			.field("max_depth", &self.max_depth())
+			.finish()
+	}
+}
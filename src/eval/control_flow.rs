@@ -0,0 +1,41 @@
+use {
+	crate::{
+		eval::{RuntimeError, RuntimeErrorKind},
+		Function, Span, Value,
+	},
+	std::rc::Rc,
+};
+
+/// Why evaluation stopped before reaching the end of the code it was running. `return`, `break`
+/// and `continue` unwind to the innermost function or loop, errors unwind all the way up.
+#[derive(Debug)]
+pub enum ControlFlow {
+	Return(Value),
+	Break,
+	Continue,
+	/// A call in tail position, which the caller runs in place of the current function instead
+	/// of nesting it. See `Expression::eval_tail`.
+	TailCall {
+		function: Rc<Function>,
+		arguments: Vec<Value>,
+		span: Span,
+	},
+	/// A `throw`, together with the error it turns into if nothing catches it.
+	Throw {
+		value: Box<Value>,
+		error: RuntimeError,
+	},
+	Error(RuntimeError),
+}
+
+impl From<RuntimeError> for ControlFlow {
+	fn from(error: RuntimeError) -> Self {
+		Self::Error(error)
+	}
+}
+
+impl From<RuntimeErrorKind> for ControlFlow {
+	fn from(kind: RuntimeErrorKind) -> Self {
+		Self::Error(kind.into())
+	}
+}
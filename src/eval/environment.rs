@@ -1,44 +1,122 @@
 use {
-	super::{builtins, builtins::BuiltinFunction},
+	super::{builtins, builtins::BuiltinFunction, CallStack},
 	crate::Value,
-	std::{cell::RefCell, collections::HashMap, rc::Rc},
+	std::{
+		cell::RefCell,
+		collections::{HashMap, HashSet},
+		rc::Rc,
+	},
 };
 
 pub type VariableStore = Rc<RefCell<HashMap<String, Value>>>;
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone)]
 pub struct Environment {
 	pub(super) variables: VariableStore,
 	pub(super) outer: Option<Rc<Environment>>,
+	pub(super) call_stack: Rc<CallStack>,
+
+	/// The names of the builtin functions, which can't be rebound. Shared like the call stack.
+	pub(super) builtins: Rc<HashSet<String>>,
 }
 
 impl Environment {
+	/// A top-level environment with the standard builtin functions.
 	pub fn new_global() -> Self {
-		let variables = HashMap::from_iter([
-			(builtins::Print.name(), Value::BuiltinFunction(Rc::new(builtins::Print))),
-			(builtins::Measure.name(), Value::BuiltinFunction(Rc::new(builtins::Measure))),
-			(builtins::First.name(), Value::BuiltinFunction(Rc::new(builtins::First))),
-		]);
+		Self::with_builtins(builtins::standard())
+	}
+
+	/// A top-level environment with the given builtin functions. If several have the same name,
+	/// the last one wins.
+	pub fn with_builtins(builtins: impl IntoIterator<Item = Rc<dyn BuiltinFunction>>) -> Self {
+		let variables = builtins
+			.into_iter()
+			.map(|builtin| (builtin.name(), Value::BuiltinFunction(builtin)))
+			.collect::<HashMap<_, _>>();
 
-		Self { variables: Rc::new(RefCell::new(variables)), ..Default::default() }
+		let builtins = variables.keys().cloned().collect();
+
+		Self {
+			variables: Rc::new(RefCell::new(variables)),
+			builtins: Rc::new(builtins),
+			..Default::default()
+		}
 	}
 
 	pub fn with_outer(outer: &Rc<Self>) -> Self {
-		Self { outer: Some(Rc::clone(&outer)), ..Default::default() }
+		Self {
+			outer: Some(Rc::clone(outer)),
+			call_stack: Rc::clone(&outer.call_stack),
+			builtins: Rc::clone(&outer.builtins),
+			..Default::default()
+		}
+	}
+
+	/// Whether `name` refers to a builtin function, and not to a binding that shadows it.
+	pub fn is_builtin(&self, name: &str) -> bool {
+		self.builtins.contains(name) && matches!(self.get(name), Some(Value::BuiltinFunction(_)))
+	}
+
+	/// The calls that are running in this environment and all environments connected to it.
+	pub fn call_stack(&self) -> &CallStack {
+		&self.call_stack
 	}
 
 	#[tracing::instrument(level = "DEBUG", fields(var = var.as_ref()), ret)]
-	pub fn get(&self, var: impl AsRef<str>) -> Value {
+	pub fn get(&self, var: impl AsRef<str>) -> Option<Value> {
 		let var = var.as_ref();
 		self.variables.borrow().get(var).map_or_else(
-			|| match &self.outer {
-				None => Value::Null,
-				Some(outer) => outer.get(var),
+			|| {
+				self.outer
+					.as_ref()
+					.and_then(|outer| outer.get(var))
 			},
-			ToOwned::to_owned,
+			|value| Some(value.to_owned()),
 		)
 	}
 
+	/// Every name that is bound in this environment or any of the environments around it.
+	pub fn names(&self) -> Vec<String> {
+		let mut names = self
+			.variables
+			.borrow()
+			.keys()
+			.cloned()
+			.collect::<Vec<_>>();
+
+		if let Some(outer) = &self.outer {
+			names.extend(outer.names());
+		}
+
+		names
+	}
+
+	/// The bindings made in this environment, sorted by name. Environments around it are ignored.
+	pub fn bindings(&self) -> Vec<(String, Value)> {
+		let mut bindings = self
+			.variables
+			.borrow()
+			.iter()
+			.map(|(name, value)| (name.clone(), value.clone()))
+			.collect::<Vec<_>>();
+
+		bindings.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+		bindings
+	}
+
+	/// Updates the nearest existing binding of `name`, looking through the environments around
+	/// this one if necessary. Returns `false` if `name` isn't bound anywhere.
+	pub fn assign(&self, name: &str, value: Value) -> bool {
+		if let Some(current) = self.variables.borrow_mut().get_mut(name) {
+			*current = value;
+			return true;
+		}
+
+		self.outer
+			.as_ref()
+			.is_some_and(|outer| outer.assign(name, value))
+	}
+
 	pub fn set(&self, name: impl Into<String>, value: impl Into<Value>) -> Value {
 		let value = value.into();
 		self.variables
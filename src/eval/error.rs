@@ -0,0 +1,290 @@
+use {
+	crate::{
+		ast::{InfixOperator, PrefixOperator},
+		eval::{builtins::Arity, Frame},
+		MapKey, Span, Value,
+	},
+	std::collections::HashMap,
+};
+
+/// An error that stopped evaluation, together with the calls that were running at that point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeError {
+	/// Boxed, since some kinds are large and errors are passed around a lot.
+	pub kind: Box<RuntimeErrorKind>,
+
+	/// The calls that were running when the error occurred, outermost first. Empty if it happened
+	/// outside of any function.
+	pub backtrace: Vec<Frame>,
+}
+
+impl RuntimeError {
+	pub fn span(&self) -> Span {
+		self.kind.span()
+	}
+
+	/// What a `catch` gets to see: the `message`, `kind` and `location` of the error.
+	pub fn into_map(self) -> HashMap<MapKey, Value> {
+		let span = self.span();
+		let location = HashMap::from([
+			(MapKey::from("line"), Value::Int(span.start.line as i64)),
+			(MapKey::from("column"), Value::Int(span.start.column as i64)),
+		]);
+
+		HashMap::from([
+			(MapKey::from("message"), Value::String(self.to_string())),
+			(MapKey::from("kind"), Value::String(self.kind.name().to_owned())),
+			(MapKey::from("location"), Value::Map(location)),
+		])
+	}
+}
+
+impl From<RuntimeErrorKind> for RuntimeError {
+	fn from(kind: RuntimeErrorKind) -> Self {
+		Self { kind: Box::new(kind), backtrace: Vec::new() }
+	}
+}
+
+impl std::fmt::Display for RuntimeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.kind)
+	}
+}
+
+impl std::error::Error for RuntimeError {
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeErrorKind {
+	/// The name of a builtin function was assigned to without shadowing it with `let` first.
+	BuiltinOverride { name: String, span: Span },
+
+	/// The identifier is not bound in the current environment.
+	UnknownIdentifier { name: String, span: Span },
+
+	/// A map key evaluated to something that can't be hashed, like a float.
+	InvalidMapKey { key: &'static str, span: Span },
+
+	/// The condition of an `if` or `while` is neither a boolean nor a number.
+	InvalidCondition { condition: &'static str, span: Span },
+
+	/// Something that is not a function was called.
+	NotCallable { value: &'static str, span: Span },
+
+	/// A function was called with the wrong number of arguments.
+	ArityMismatch { expected: Arity, got: usize, span: Span },
+
+	/// A builtin function was passed an argument of a type it doesn't accept. `position` starts
+	/// at 1.
+	InvalidArgument {
+		function: String,
+		position: usize,
+		expected: String,
+		got: &'static str,
+		span: Span,
+	},
+
+	/// A prefix operator was applied to a value of the wrong type.
+	InvalidPrefixOperand { operator: PrefixOperator, operand: &'static str, span: Span },
+
+	/// An infix operator was applied to values of incompatible types.
+	TypeMismatch { operator: InfixOperator, lhs: &'static str, rhs: &'static str, span: Span },
+
+	/// A string was repeated a negative amount of times.
+	NegativeRepetition { count: i64, span: Span },
+
+	/// The value cannot be indexed with the given index.
+	InvalidIndex { lhs: &'static str, index: &'static str, span: Span },
+
+	/// A `for` loop was given something that can't be iterated over.
+	NotIterable { value: &'static str, span: Span },
+
+	/// The bounds of a range are not integers.
+	InvalidRange { start: &'static str, end: &'static str, span: Span },
+
+	/// An array element past its end was assigned to.
+	IndexOutOfBounds { index: i64, length: usize, span: Span },
+
+	/// An integer was divided by zero, either through `/` or `%`.
+	DivisionByZero { operator: InfixOperator, lhs: String, span: Span },
+
+	/// A `return` or `?` was evaluated outside of a function. The parser doesn't allow this, but
+	/// programs built by hand can still contain one.
+	ReturnOutsideFunction { span: Span },
+
+	/// A `break` or `continue` reached the end of a function or the program without meeting a
+	/// loop, like [`RuntimeErrorKind::ReturnOutsideFunction`].
+	LoopControlOutsideLoop { keyword: &'static str, span: Span },
+
+	/// Calls were nested deeper than the interpreter allows.
+	StackOverflow { limit: usize, span: Span },
+
+	/// A builtin that changes its first argument was given something other than a variable or an
+	/// element of one.
+	ImmutableArgument { function: String, span: Span },
+
+	/// A `throw` that no `catch` handled.
+	Thrown { message: String, span: Span },
+
+	/// A builtin function failed for a reason of its own.
+	Builtin { message: String, span: Span },
+}
+
+impl RuntimeErrorKind {
+	pub fn span(&self) -> Span {
+		match self {
+			RuntimeErrorKind::BuiltinOverride { span, .. }
+			| RuntimeErrorKind::UnknownIdentifier { span, .. }
+			| RuntimeErrorKind::InvalidMapKey { span, .. }
+			| RuntimeErrorKind::InvalidCondition { span, .. }
+			| RuntimeErrorKind::NotCallable { span, .. }
+			| RuntimeErrorKind::ArityMismatch { span, .. }
+			| RuntimeErrorKind::InvalidArgument { span, .. }
+			| RuntimeErrorKind::InvalidPrefixOperand { span, .. }
+			| RuntimeErrorKind::TypeMismatch { span, .. }
+			| RuntimeErrorKind::NegativeRepetition { span, .. }
+			| RuntimeErrorKind::InvalidIndex { span, .. }
+			| RuntimeErrorKind::NotIterable { span, .. }
+			| RuntimeErrorKind::InvalidRange { span, .. }
+			| RuntimeErrorKind::IndexOutOfBounds { span, .. }
+			| RuntimeErrorKind::DivisionByZero { span, .. }
+			| RuntimeErrorKind::ReturnOutsideFunction { span }
+			| RuntimeErrorKind::LoopControlOutsideLoop { span, .. }
+			| RuntimeErrorKind::StackOverflow { span, .. }
+			| RuntimeErrorKind::ImmutableArgument { span, .. }
+			| RuntimeErrorKind::Thrown { span, .. }
+			| RuntimeErrorKind::Builtin { span, .. } => *span,
+		}
+	}
+
+	/// The name of the variant, which Monkey code sees as the `kind` of a caught error.
+	pub fn name(&self) -> &'static str {
+		match self {
+			RuntimeErrorKind::BuiltinOverride { .. } => "BuiltinOverride",
+			RuntimeErrorKind::UnknownIdentifier { .. } => "UnknownIdentifier",
+			RuntimeErrorKind::InvalidMapKey { .. } => "InvalidMapKey",
+			RuntimeErrorKind::InvalidCondition { .. } => "InvalidCondition",
+			RuntimeErrorKind::NotCallable { .. } => "NotCallable",
+			RuntimeErrorKind::ArityMismatch { .. } => "ArityMismatch",
+			RuntimeErrorKind::InvalidArgument { .. } => "InvalidArgument",
+			RuntimeErrorKind::InvalidPrefixOperand { .. } => "InvalidPrefixOperand",
+			RuntimeErrorKind::TypeMismatch { .. } => "TypeMismatch",
+			RuntimeErrorKind::NegativeRepetition { .. } => "NegativeRepetition",
+			RuntimeErrorKind::InvalidIndex { .. } => "InvalidIndex",
+			RuntimeErrorKind::NotIterable { .. } => "NotIterable",
+			RuntimeErrorKind::InvalidRange { .. } => "InvalidRange",
+			RuntimeErrorKind::IndexOutOfBounds { .. } => "IndexOutOfBounds",
+			RuntimeErrorKind::DivisionByZero { .. } => "DivisionByZero",
+			RuntimeErrorKind::ReturnOutsideFunction { .. } => "ReturnOutsideFunction",
+			RuntimeErrorKind::LoopControlOutsideLoop { .. } => "LoopControlOutsideLoop",
+			RuntimeErrorKind::StackOverflow { .. } => "StackOverflow",
+			RuntimeErrorKind::ImmutableArgument { .. } => "ImmutableArgument",
+			RuntimeErrorKind::Thrown { .. } => "Thrown",
+			RuntimeErrorKind::Builtin { .. } => "Builtin",
+		}
+	}
+}
+
+impl std::fmt::Display for RuntimeErrorKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			RuntimeErrorKind::BuiltinOverride { name, .. } => {
+				write!(f, "Cannot override builtin value `{name}`")
+			}
+			RuntimeErrorKind::UnknownIdentifier { name, .. } => {
+				write!(f, "Unknown identifier `{name}`")
+			}
+			RuntimeErrorKind::InvalidMapKey { key, .. } => {
+				write!(f, "Map keys must be ints, bools, strings or arrays of those, not `{key}`")
+			}
+			RuntimeErrorKind::InvalidCondition { condition, .. } => {
+				write!(f, "Expected boolean expression inside conditional but got `{condition}`")
+			}
+			RuntimeErrorKind::NotCallable { value, .. } => {
+				write!(f, "Expected function before call expression but got `{value}`")
+			}
+			RuntimeErrorKind::ArityMismatch { expected, got, .. }
+				if expected.max().is_some_and(|max| *got > max) =>
+			{
+				write!(f, "Too many arguments! Expected {expected} but got {got}")
+			}
+			RuntimeErrorKind::ArityMismatch { expected, got, .. } => {
+				write!(f, "Not enough arguments! Expected {expected} but got {got}")
+			}
+			RuntimeErrorKind::InvalidArgument { function, position, expected, got, .. } => {
+				write!(f, "Argument {position} of `{function}`: expected {expected}, got {got}")
+			}
+			RuntimeErrorKind::InvalidPrefixOperand { operator, operand, .. } => match operator {
+				PrefixOperator::Not => write!(f, "`{operand}` cannot be inverted"),
+				PrefixOperator::Neg => write!(f, "`{operand}` cannot be negated"),
+			},
+			RuntimeErrorKind::TypeMismatch { operator, lhs, rhs, .. } => {
+				use InfixOperator as Op;
+
+				match (*lhs, *rhs, operator) {
+					("null", "null", _) => {
+						write!(f, "Cannot perform operation `{operator:?}` on null")
+					}
+					("bool", "bool", _) => {
+						write!(f, "Cannot perform operation `{operator:?}` on a boolean")
+					}
+					("int", "bool", _) | ("bool", "int", _) => write!(
+						f,
+						"Cannot perform operation `{operator:?}` on a boolean and integer combo"
+					),
+					("string", "string", Op::Sub) => {
+						write!(f, "Cannot subtract one string from another")
+					}
+					("string", "string", Op::Mul) => write!(f, "Cannot multiply strings"),
+					("string", "string", Op::Div) => write!(f, "Cannot divide strings"),
+					("string", "string", Op::Modulo) => write!(f, "Cannot modulo strings"),
+					("string", "int", Op::Sub) | ("int", "string", Op::Sub) => {
+						write!(f, "Cannot perform subtraction on strings")
+					}
+					("string", "int", Op::Div) | ("int", "string", Op::Div) => {
+						write!(f, "Cannot perform division on strings")
+					}
+					("string", "int", Op::Modulo) | ("int", "string", Op::Modulo) => {
+						write!(f, "Cannot perform modulo on strings")
+					}
+					("string", "int", _) | ("int", "string", _) => {
+						write!(f, "Cannot compare string and number")
+					}
+					_ => write!(f, "Cannot evaluate `{lhs} {operator} {rhs}`"),
+				}
+			}
+			RuntimeErrorKind::NegativeRepetition { .. } => {
+				write!(f, "Cannot multiply a string by a negative number")
+			}
+			RuntimeErrorKind::InvalidIndex { .. } => write!(f, "Invalid index operator access"),
+			RuntimeErrorKind::NotIterable { value, .. } => {
+				write!(f, "Cannot iterate over `{value}`")
+			}
+			RuntimeErrorKind::InvalidRange { start, end, .. } => {
+				write!(f, "Range bounds must be integers but got `{start}` and `{end}`")
+			}
+			RuntimeErrorKind::IndexOutOfBounds { index, length, .. } => {
+				write!(f, "Index {index} is out of bounds for an array of length {length}")
+			}
+			RuntimeErrorKind::DivisionByZero { operator, lhs, .. } => {
+				write!(f, "Division by zero in `{lhs} {operator} 0`")
+			}
+			RuntimeErrorKind::ReturnOutsideFunction { .. } => {
+				write!(f, "Cannot return outside of a function")
+			}
+			RuntimeErrorKind::LoopControlOutsideLoop { keyword, .. } => {
+				write!(f, "`{keyword}` cannot be used outside of a loop")
+			}
+			RuntimeErrorKind::StackOverflow { limit, .. } => {
+				write!(f, "Maximum recursion depth of {limit} exceeded")
+			}
+			RuntimeErrorKind::ImmutableArgument { function, .. } => {
+				write!(f, "`{function}` changes its first argument, so it has to be a variable")
+			}
+			RuntimeErrorKind::Thrown { message, .. }
+			| RuntimeErrorKind::Builtin { message, .. } => {
+				write!(f, "{message}")
+			}
+		}
+	}
+}
@@ -4,19 +4,43 @@ mod tests;
 use {
 	crate::{
 		ast::{InfixOperator, PrefixOperator},
-		Expression, Program, Statement, Value,
+		yeet, Expression, ExpressionKind, Function, MapKey, Program, Span, Statement,
+		StatementKind, Value,
 	},
-	color_eyre::{eyre::bail as yeet, Result},
-	std::rc::Rc,
+	num_bigint::BigInt,
+	num_traits::{ToPrimitive, Zero},
+	std::{collections::HashMap, rc::Rc},
 };
 
 mod environment;
-use std::collections::HashMap;
-
 pub use environment::Environment;
 
+mod error;
+pub use error::{RuntimeError, RuntimeErrorKind};
+
+mod control_flow;
+use control_flow::ControlFlow;
+
+mod call_stack;
+pub use call_stack::{CallStack, Frame, DEFAULT_MAX_DEPTH};
+
+type Result<T, E = RuntimeError> = std::result::Result<T, E>;
+
+/// The result of evaluating a part of a program, which may be cut short by [`ControlFlow`].
+type Flow<T> = Result<T, ControlFlow>;
+
+/// How much native stack has to be left before starting a call, in bytes.
+const STACK_RED_ZONE: usize = 256 * 1024;
+
+/// How much native stack to allocate whenever it runs low, in bytes.
+const STACK_SEGMENT_SIZE: usize = 4 * 1024 * 1024;
+
+/// The indices leading from a variable to an element nested inside of it, e.g. `[0, "b"]` for
+/// `a[0]["b"]`, together with the span of each index and of the index expression around it.
+type IndexPath = Vec<(Value, Span, Span)>;
+
 pub mod builtins;
-use builtins::BUILTINS;
+use builtins::{Arity, BuiltinFunction, Context};
 
 pub trait Eval {
 	fn eval(self, environment: Rc<Environment>) -> Result<Value>;
@@ -28,247 +52,672 @@ impl Eval for Program {
 		let mut result = Value::Null;
 
 		for statement in self.statements {
-			result = statement.eval(Rc::clone(&environment))?;
-
-			if let Value::Return(value) = result {
-				return Ok(*value);
-			}
+			// The parser rejects `return`, `break` and `continue` in the wrong places, but programs
+			// can also be put together by hand.
+			let span = statement.span;
+			result = match statement.eval(Rc::clone(&environment)) {
+				Ok(value) => value,
+				Err(ControlFlow::Error(error) | ControlFlow::Throw { error, .. }) => yeet!(error),
+				Err(ControlFlow::Return(_) | ControlFlow::TailCall { .. }) => {
+					yeet!(RuntimeErrorKind::ReturnOutsideFunction { span })
+				}
+				Err(ControlFlow::Break) => {
+					yeet!(RuntimeErrorKind::LoopControlOutsideLoop { keyword: "break", span })
+				}
+				Err(ControlFlow::Continue) => {
+					yeet!(RuntimeErrorKind::LoopControlOutsideLoop { keyword: "continue", span })
+				}
+			};
 		}
 
 		Ok(result)
 	}
 }
 
-impl Eval for Statement {
+impl Statement {
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn eval(self, environment: Rc<Environment>) -> Result<Value> {
-		match self {
-			Statement::Expression(value) => value.eval(environment),
-
-			Statement::Let { name, value } => {
-				if BUILTINS.contains(&*name) {
-					yeet!("Cannot override builtin value `{name}`");
-				}
+	fn eval(self, environment: Rc<Environment>) -> Flow<Value> {
+		match self.kind {
+			StatementKind::Expression(value) => value.eval(environment),
+
+			StatementKind::Let { name, value } => {
+				// Functions defined right here are named after their binding.
+				let value = match value.kind {
+					ExpressionKind::Function { parameters, body } => {
+						Value::Function(Rc::new(Function {
+							name: Some(name.clone()),
+							parameters,
+							body,
+							environment: Rc::clone(&environment),
+						}))
+					}
+					kind => Expression::new(kind, value.span).eval(Rc::clone(&environment))?,
+				};
 
-				let value = value.eval(Rc::clone(&environment))?;
 				let value = environment.set(name, value);
 				Ok(value)
 			}
 
-			Statement::Return { value } => value
-				.eval(environment)
-				.map(|value| Value::Return(Box::new(value))),
+			StatementKind::Return { value } => {
+				Err(ControlFlow::Return(value.eval_tail(environment)?))
+			}
+			StatementKind::Throw { value } => {
+				let value = value.eval(environment)?;
+				let message = match &value {
+					Value::String(message) => message.clone(),
+					Value::Map(map) => match map.get(&MapKey::from("message")) {
+						Some(Value::String(message)) => message.clone(),
+						_ => value.to_string(),
+					},
+					value => value.to_string(),
+				};
+
+				let error = RuntimeErrorKind::Thrown { message, span: self.span }.into();
+				Err(ControlFlow::Throw { value: Box::new(value), error })
+			}
+			StatementKind::Break => Err(ControlFlow::Break),
+			StatementKind::Continue => Err(ControlFlow::Continue),
 
-			Statement::Block(statements) => {
+			StatementKind::Block(statements) => {
 				let mut result = Value::Null;
 
 				for statement in statements {
 					result = statement.eval(Rc::clone(&environment))?;
-
-					if matches!(result, Value::Return(_)) {
-						return Ok(result);
-					}
 				}
 
 				Ok(result)
 			}
 		}
 	}
+
+	/// Like [`Statement::eval`], for statements whose value is returned from a function.
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_tail(self, environment: Rc<Environment>) -> Flow<Value> {
+		match self.kind {
+			StatementKind::Expression(value) => value.eval_tail(environment),
+			StatementKind::Block(mut statements) => {
+				let Some(last) = statements.pop() else {
+					return Ok(Value::Null);
+				};
+
+				for statement in statements {
+					statement.eval(Rc::clone(&environment))?;
+				}
+
+				last.eval_tail(environment)
+			}
+			kind => Statement::new(kind, self.span).eval(environment),
+		}
+	}
 }
 
-impl Eval for Expression {
+impl Expression {
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn eval(self, environment: Rc<Environment>) -> Result<Value> {
-		Ok(match self {
-			Expression::Int(int) => Value::Int(int),
-			Expression::Bool(bool) => Value::Bool(bool),
-			Expression::Identifier(identifier) if identifier == "null" => Value::Null,
-			Expression::Identifier(identifier) => environment.get(&identifier),
-			Expression::String(string) => Value::String(string),
-			Expression::Array(array) => Value::Array(
+	fn eval(self, environment: Rc<Environment>) -> Flow<Value> {
+		let span = self.span;
+
+		Ok(match self.kind {
+			ExpressionKind::Int(int) => Value::Int(int),
+			ExpressionKind::BigInt(int) => Value::from(int),
+			ExpressionKind::Float(float) => Value::Float(float),
+			ExpressionKind::Bool(bool) => Value::Bool(bool),
+			ExpressionKind::Identifier(identifier) if identifier == "null" => Value::Null,
+			ExpressionKind::Identifier(identifier) => match environment.get(&identifier) {
+				Some(value) => value,
+				None => yeet!(RuntimeErrorKind::UnknownIdentifier { name: identifier, span }),
+			},
+			ExpressionKind::String(string) => Value::String(string),
+			ExpressionKind::Array(array) => Value::Array(
 				array
 					.into_iter()
 					.map(|value| value.eval(Rc::clone(&environment)))
-					.collect::<Result<Vec<_>>>()?,
+					.collect::<Flow<Vec<_>>>()?,
 			),
-			Expression::Map(pairs) => {
+			ExpressionKind::Map(pairs) => {
 				let pairs = pairs
 					.into_iter()
 					.map(|(k, v)| {
-						let Value::String(k) = k.eval(Rc::clone(&environment))? else {
-							yeet!("Key in map must resolve to a string");
-						};
-
+						let key_span = k.span;
+						let k = Expression::eval_key(k.eval(Rc::clone(&environment))?, key_span)?;
 						let v = v.eval(Rc::clone(&environment))?;
 						Ok((k, v))
 					})
-					.collect::<Result<HashMap<_, _>>>()?;
+					.collect::<Flow<HashMap<_, _>>>()?;
 
 				Value::Map(pairs)
 			}
-			Expression::Condition { condition, consequence, alternative } => {
-				let condition = match condition.eval(Rc::clone(&environment))? {
-					Value::Bool(bool) => bool,
-					Value::Int(int) => int != 0,
-					condition => yeet!(
-						"Expected boolean expression inside conditional but got `{condition:?}`"
-					),
-				};
-
-				if condition {
-					Statement::Block(consequence).eval(environment)
+			ExpressionKind::Condition { condition, consequence, alternative } => {
+				if Expression::eval_condition(*condition, &environment)? {
+					Statement::from(StatementKind::Block(consequence)).eval(environment)
 				} else {
 					alternative.map_or(Ok(Value::Null), |statements| {
-						Statement::Block(statements).eval(environment)
+						Statement::from(StatementKind::Block(statements)).eval(environment)
 					})
 				}?
 			}
-			Expression::Function { parameters, body } => Value::Function {
-				parameters,
-				body,
-				environment: Environment::with_outer(&environment),
-			},
-			Expression::Call { function, arguments } => {
-				let evaluated = function.eval(Rc::clone(&environment))?;
-
-				if let Value::BuiltinFunction(function) = evaluated {
-					let arguments = arguments
-						.into_iter()
-						.map(|arg| arg.eval(Rc::clone(&environment)))
-						.collect::<Result<Vec<_>>>()?;
-
-					let result = function.call(arguments);
-					return Ok(result);
+			ExpressionKind::While { condition, body } => {
+				while Expression::eval_condition((*condition).clone(), &environment)? {
+					if Expression::eval_loop_body(&body, &environment)? {
+						break;
+					}
 				}
 
-				let Value::Function { parameters, body, environment: local_env } = evaluated else {
-					yeet!("Expected function before call expression but got `{evaluated:?}`");
+				Value::Null
+			}
+			ExpressionKind::For { variables, iterable, body } => {
+				let iterable_span = iterable.span;
+				let iterable = iterable.eval(Rc::clone(&environment))?;
+
+				// With a single variable, maps give us their keys and everything else its items.
+				let (key_name, value_name) = match variables.as_slice() {
+					[key, value] => (Some(key), Some(value)),
+					[key] if matches!(iterable, Value::Map(_)) => (Some(key), None),
+					[value] => (None, Some(value)),
+					_ => unreachable!("the parser only accepts one or two loop variables"),
 				};
 
-				let local_env = Rc::new(local_env);
-				let local_env = Rc::new(Environment::with_outer(&local_env));
+				for (key, value) in Expression::iterate(iterable, iterable_span)? {
+					// Every iteration gets its own scope, so closures keep the values they saw.
+					let scope = Rc::new(Environment::with_outer(&environment));
 
-				let arguments = arguments
-					.into_iter()
-					.map(|arg| arg.eval(Rc::clone(&local_env)))
-					.collect::<Result<Vec<_>>>()?;
-
-				let n_params = parameters.len();
-				let n_args = arguments.len();
-				if n_params < n_args {
-					yeet!("Too many arguments! Expected {n_params} but got {n_args}");
-				} else if n_params > n_args {
-					yeet!("Not enough arguments! Expected {n_params} but got {n_args}");
-				}
+					if let Some(name) = key_name {
+						scope.set(name, key);
+					}
 
-				for (param, arg) in parameters.into_iter().zip(arguments) {
-					local_env.set(param, arg);
+					if let Some(name) = value_name {
+						scope.set(name, value);
+					}
+
+					if Expression::eval_loop_body(&body, &scope)? {
+						break;
+					}
 				}
 
-				let body = match Statement::Block(body).eval(local_env)? {
-					Value::Return(value) => *value,
-					value => value,
+				Value::Null
+			}
+			ExpressionKind::Try { body, variable, handler, finally } => {
+				let caught = match Expression::eval_now(body, &environment) {
+					Err(ControlFlow::Error(error)) => Ok(Value::Map(error.into_map())),
+					// Thrown maps are passed along untouched, so rethrowing a caught error keeps it.
+					Err(ControlFlow::Throw { value, .. }) if matches!(*value, Value::Map(_)) => {
+						Ok(*value)
+					}
+					Err(ControlFlow::Throw { value, error }) => {
+						let mut map = error.into_map();
+						map.insert(MapKey::from("value"), *value);
+						Ok(Value::Map(map))
+					}
+					result => Err(result),
 				};
 
-				body
+				let result = match caught {
+					Ok(error) => {
+						let scope = Rc::new(Environment::with_outer(&environment));
+						scope.set(variable, error);
+						Expression::eval_now(handler, &scope)
+					}
+					Err(result) => result,
+				};
+
+				// Anything that stops `finally` early, like an error or `return`, wins over the result.
+				if let Some(finally) = finally {
+					Statement::from(StatementKind::Block(finally)).eval(Rc::clone(&environment))?;
+				}
+
+				result?
+			}
+			ExpressionKind::Range { start, end, inclusive } => {
+				let start = start.eval(Rc::clone(&environment))?;
+				let end = end.eval(environment)?;
+
+				match (start, end) {
+					(Value::Int(start), Value::Int(end)) => Value::Range { start, end, inclusive },
+					(start, end) => yeet!(RuntimeErrorKind::InvalidRange {
+						start: start.type_name(),
+						end: end.type_name(),
+						span,
+					}),
+				}
+			}
+			ExpressionKind::Function { parameters, body } => {
+				Value::Function(Rc::new(Function { name: None, parameters, body, environment }))
 			}
-			Expression::Prefix { operator, rhs } => {
+			ExpressionKind::Call { function, arguments } => {
+				match function.eval(Rc::clone(&environment))? {
+					Value::BuiltinFunction(function) if function.mutates() => {
+						Expression::call_mutating(function, arguments, &environment, span)?
+					}
+					function => {
+						let arguments = Expression::eval_arguments(arguments, &environment)?;
+						Expression::call(function, arguments, span)?
+					}
+				}
+			}
+			ExpressionKind::Prefix { operator, rhs } => {
 				let rhs = rhs.eval(environment)?;
-				Expression::eval_prefix(operator, rhs)?
+				Expression::eval_prefix(operator, rhs, span)?
 			}
-			Expression::Infix { operator, lhs, rhs } => {
+			ExpressionKind::Infix { operator, lhs, rhs } => {
 				let lhs = lhs.eval(Rc::clone(&environment))?;
 				let rhs = rhs.eval(environment)?;
-				Expression::eval_infix(operator, lhs, rhs)?
+				Expression::eval_infix(operator, lhs, rhs, span)?
 			}
-			Expression::Index { lhs, idx } => {
+			ExpressionKind::Index { lhs, idx } => {
 				let lhs = lhs.eval(Rc::clone(&environment))?;
+				let idx_span = idx.span;
 				let idx = idx.eval(environment)?;
+				Expression::eval_index(lhs, idx, idx_span, span)?
+			}
+			ExpressionKind::Propagate { value } => match value.eval(environment)? {
+				error @ Value::Error(_) => return Err(ControlFlow::Return(error)),
+				value => value,
+			},
+			ExpressionKind::Assign { target, operator, value } => {
+				let (name, name_span, path) = Expression::eval_target(*target, &environment)?;
+
+				// Builtins can be shadowed with `let`, but not replaced by accident.
+				if environment.is_builtin(&name) {
+					yeet!(RuntimeErrorKind::BuiltinOverride { name, span: name_span });
+				}
+
+				let value = value.eval(Rc::clone(&environment))?;
+				let Some(root) = environment.get(&name) else {
+					yeet!(RuntimeErrorKind::UnknownIdentifier { name, span: name_span });
+				};
+
+				let value = match operator {
+					Some(operator) => {
+						let current = Expression::eval_path(root.clone(), &path)?;
+						Expression::eval_infix(operator, current, value, span)?
+					}
+					None => value,
+				};
+
+				let root = Expression::assign_path(root, &path, value.clone())?;
+				environment.assign(&name, root);
+
+				value
+			}
+		})
+	}
+
+	/// Like [`Expression::eval`], for expressions whose value is returned from a function. Calls
+	/// to Monkey functions become a [`ControlFlow::TailCall`], so that the function making them is
+	/// done before they run and recursion doesn't grow the stack.
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_tail(self, environment: Rc<Environment>) -> Flow<Value> {
+		let span = self.span;
+
+		match self.kind {
+			ExpressionKind::Call { function, arguments } => {
+				let function = function.eval(Rc::clone(&environment))?;
+				if let Value::BuiltinFunction(function) = &function {
+					if function.mutates() {
+						let function = Rc::clone(function);
+						return Expression::call_mutating(function, arguments, &environment, span);
+					}
+				}
+
+				let arguments = Expression::eval_arguments(arguments, &environment)?;
+				match function {
+					Value::Function(function) => {
+						Err(ControlFlow::TailCall { function, arguments, span })
+					}
+					function => Expression::call(function, arguments, span),
+				}
+			}
+			ExpressionKind::Condition { condition, consequence, alternative } => {
+				let block = if Expression::eval_condition(*condition, &environment)? {
+					consequence
+				} else {
+					alternative.unwrap_or_default()
+				};
+
+				Statement::from(StatementKind::Block(block)).eval_tail(environment)
+			}
+			kind => Expression::new(kind, span).eval(environment),
+		}
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_arguments(
+		arguments: Vec<Expression>,
+		environment: &Rc<Environment>,
+	) -> Flow<Vec<Value>> {
+		arguments
+			.into_iter()
+			.map(|arg| arg.eval(Rc::clone(environment)))
+			.collect()
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn call(function: Value, arguments: Vec<Value>, span: Span) -> Flow<Value> {
+		let function = match function {
+			Value::Function(function) => function,
+			Value::BuiltinFunction(function) => {
+				builtins::check_arguments(&*function, &arguments, span)?;
+				let context = Context::new(span);
+				return match function.call(&context, arguments) {
+					Ok(value) => Ok(value),
+					Err(error) => Err(context.rethrow(error)),
+				};
+			}
+			function => yeet!(RuntimeErrorKind::NotCallable { value: function.type_name(), span }),
+		};
+
+		let call_stack = Rc::clone(&function.environment.call_stack);
+		call_stack.push(Frame { name: function.name.clone(), span, arguments: arguments.len() })?;
+
+		// Evaluating a single call takes a lot of native stack, so we make sure there is room for
+		// as many calls as the call stack allows.
+		let mut result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+			Expression::run_calls(function, arguments, span, &call_stack)
+		});
+
+		// The innermost call an error passes through knows the whole stack.
+		if let Err(ControlFlow::Error(error) | ControlFlow::Throw { error, .. }) = &mut result {
+			if error.backtrace.is_empty() {
+				error.backtrace = call_stack.frames();
+			}
+		}
 
-				match (lhs, idx) {
-					(Value::Array(array), Value::Int(idx)) => Expression::eval_array(array, idx)?,
-					(Value::Map(map), Value::String(key)) => Expression::eval_map(map, key)?,
+		call_stack.pop();
+		result
+	}
+
+	/// Calls a builtin that changes its first argument, and writes the changed value back to the
+	/// variable the argument came from.
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn call_mutating(
+		function: Rc<dyn BuiltinFunction>,
+		mut arguments: Vec<Expression>,
+		environment: &Rc<Environment>,
+		span: Span,
+	) -> Flow<Value> {
+		let expected = function.arity();
+		let got = arguments.len();
+		if got == 0 || !expected.accepts(got) {
+			yeet!(RuntimeErrorKind::ArityMismatch { expected, got, span });
+		}
+
+		let target = arguments.remove(0);
+		if !target.is_assignable() {
+			yeet!(RuntimeErrorKind::ImmutableArgument {
+				function: function.name(),
+				span: target.span
+			});
+		}
+
+		let (name, name_span, path) = Expression::eval_target(target, environment)?;
+		let Some(root) = environment.get(&name) else {
+			yeet!(RuntimeErrorKind::UnknownIdentifier { name, span: name_span });
+		};
+
+		let mut arguments = Expression::eval_arguments(arguments, environment)?;
+		arguments.insert(0, Expression::eval_path(root.clone(), &path)?);
+		builtins::check_arguments(&*function, &arguments, span)?;
+
+		let mut target = arguments.remove(0);
+		let context = Context::new(span);
+		let value = match function.call_mut(&context, &mut target, arguments) {
+			Ok(value) => value,
+			Err(error) => return Err(context.rethrow(error)),
+		};
+
+		let root = Expression::assign_path(root, &path, target)?;
+		environment.assign(&name, root);
+
+		Ok(value)
+	}
+
+	/// Runs `function` and then every tail call it makes, one after the other.
+	fn run_calls(
+		mut function: Rc<Function>,
+		mut arguments: Vec<Value>,
+		mut span: Span,
+		call_stack: &CallStack,
+	) -> Flow<Value> {
+		// Tail calls take over the frame of the call they replace.
+		loop {
+			let expected = Arity::Fixed(function.parameters.len());
+			let got = arguments.len();
+			if !expected.accepts(got) {
+				yeet!(RuntimeErrorKind::ArityMismatch { expected, got, span });
+			}
 
-					_ => yeet!("Invalid index operator access"),
+			// Every call gets a fresh scope for its parameters and local bindings, on top of the
+			// scope the function was defined in.
+			let local_env = Rc::new(Environment::with_outer(&function.environment));
+			for (param, arg) in function.parameters.iter().zip(arguments) {
+				local_env.set(param, arg);
+			}
+
+			let body = Statement::from(StatementKind::Block(function.body.clone()));
+			match body.eval_tail(local_env) {
+				Ok(value) | Err(ControlFlow::Return(value)) => return Ok(value),
+				Err(ControlFlow::TailCall {
+					function: next,
+					arguments: next_arguments,
+					span: next_span,
+				}) => {
+					call_stack.replace(Frame {
+						name: next.name.clone(),
+						span: next_span,
+						arguments: next_arguments.len(),
+					});
+
+					function = next;
+					arguments = next_arguments;
+					span = next_span;
+				}
+				Err(ControlFlow::Break) => {
+					yeet!(RuntimeErrorKind::LoopControlOutsideLoop { keyword: "break", span })
+				}
+				Err(ControlFlow::Continue) => {
+					yeet!(RuntimeErrorKind::LoopControlOutsideLoop { keyword: "continue", span })
 				}
+				Err(error) => return Err(error),
+			}
+		}
+	}
+
+	/// Evaluates the block of a `try` or `catch`. A `return f(x)` inside of it calls `f` right away
+	/// instead of leaving that to the caller, so that it still runs inside of the `try`.
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_now(block: Vec<Statement>, environment: &Rc<Environment>) -> Flow<Value> {
+		match Statement::from(StatementKind::Block(block)).eval(Rc::clone(environment)) {
+			Err(ControlFlow::TailCall { function, arguments, span }) => {
+				let value = Expression::call(Value::Function(function), arguments, span)?;
+				Err(ControlFlow::Return(value))
+			}
+			result => result,
+		}
+	}
+
+	/// Evaluates the condition of an `if` or `while`.
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_condition(condition: Expression, environment: &Rc<Environment>) -> Flow<bool> {
+		let span = condition.span;
+
+		let condition = condition.eval(Rc::clone(environment))?;
+		match condition.truthiness() {
+			Some(bool) => Ok(bool),
+			None => {
+				yeet!(RuntimeErrorKind::InvalidCondition { condition: condition.type_name(), span })
+			}
+		}
+	}
+
+	/// Runs a single iteration of a loop. Returns whether the loop has to stop because of a
+	/// `break`.
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_loop_body(body: &[Statement], environment: &Rc<Environment>) -> Flow<bool> {
+		let block = Statement::from(StatementKind::Block(body.to_vec()));
+
+		match block.eval(Rc::clone(environment)) {
+			Ok(_) | Err(ControlFlow::Continue) => Ok(false),
+			Err(ControlFlow::Break) => Ok(true),
+			Err(flow) => Err(flow),
+		}
+	}
+
+	/// The `(key, value)` pairs a `for` loop goes through. Keys are indices, except for maps, which
+	/// are walked in the order of their keys.
+	#[tracing::instrument(level = "DEBUG")]
+	fn iterate(iterable: Value, span: Span) -> Result<Box<dyn Iterator<Item = (Value, Value)>>> {
+		let index = |(index, value)| (Value::Int(index as i64), value);
+
+		Ok(match iterable {
+			Value::Array(array) => Box::new(array.into_iter().enumerate().map(index)),
+			Value::String(string) => {
+				let chars = string.chars().collect::<Vec<_>>();
+				Box::new(
+					chars
+						.into_iter()
+						.map(String::from)
+						.map(Value::String)
+						.enumerate()
+						.map(index),
+				)
 			}
+			Value::Map(map) => Box::new(
+				Expression::sorted_entries(map)
+					.into_iter()
+					.map(|(key, value)| (Value::from(key), value)),
+			),
+			Value::Range { start, end, inclusive: false } => Box::new(
+				(start..end)
+					.map(Value::Int)
+					.enumerate()
+					.map(index),
+			),
+			Value::Range { start, end, inclusive: true } => Box::new(
+				(start..=end)
+					.map(Value::Int)
+					.enumerate()
+					.map(index),
+			),
+			value => yeet!(RuntimeErrorKind::NotIterable { value: value.type_name(), span }),
 		})
 	}
-}
 
-impl Expression {
+	/// The entries of `map`, in the order of their keys.
+	fn sorted_entries(map: HashMap<MapKey, Value>) -> Vec<(MapKey, Value)> {
+		let mut entries = map.into_iter().collect::<Vec<_>>();
+		entries.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+		entries
+	}
+
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn eval_prefix(operator: PrefixOperator, rhs: Value) -> Result<Value> {
+	fn eval_prefix(operator: PrefixOperator, rhs: Value, span: Span) -> Result<Value> {
 		Ok(match operator {
 			PrefixOperator::Not => Self::eval_bang(rhs),
-			PrefixOperator::Neg => Self::eval_neg(rhs)?,
+			PrefixOperator::Neg => Self::eval_neg(rhs, span)?,
 		})
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
 	fn eval_bang(rhs: Value) -> Value {
 		Value::Bool(match rhs {
-			Value::Null => true,
-			Value::Return(value) => return Self::eval_bang(*value),
+			Value::Null | Value::Error(_) => true,
 			Value::Int(int) => int == 0,
+			Value::BigInt(_) => false,
+			Value::Float(float) => float == 0.0,
 			Value::Bool(bool) => !bool,
 			Value::String(_)
 			| Value::Array(_)
 			| Value::Map(_)
-			| Value::Function { .. }
+			| Value::Range { .. }
+			| Value::Function(_)
 			| Value::BuiltinFunction(_) => false,
 		})
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn eval_neg(rhs: Value) -> Result<Value> {
-		Ok(Value::Int(match rhs {
-			Value::Int(int) => -int,
-			Value::Return(value) => return Self::eval_neg(*value),
+	fn eval_neg(rhs: Value, span: Span) -> Result<Value> {
+		Ok(match rhs {
+			Value::Int(int) => match int.checked_neg() {
+				Some(int) => Value::Int(int),
+				None => Value::from(-BigInt::from(int)),
+			},
+			Value::BigInt(int) => Value::from(-int),
+			Value::Float(float) => Value::Float(-float),
 			Value::Null
 			| Value::Bool(_)
 			| Value::String(_)
 			| Value::Array(_)
 			| Value::Map(_)
-			| Value::Function { .. }
-			| Value::BuiltinFunction(_) => {
-				yeet!("`{rhs:?}` cannot be negated");
+			| Value::Range { .. }
+			| Value::Function(_)
+			| Value::BuiltinFunction(_)
+			| Value::Error(_) => {
+				yeet!(RuntimeErrorKind::InvalidPrefixOperand {
+					operator: PrefixOperator::Neg,
+					operand: rhs.type_name(),
+					span,
+				});
 			}
-		}))
+		})
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn eval_infix(operator: InfixOperator, lhs: Value, rhs: Value) -> Result<Value> {
+	fn eval_infix(operator: InfixOperator, lhs: Value, rhs: Value, span: Span) -> Result<Value> {
+		let mismatch = RuntimeErrorKind::TypeMismatch {
+			operator,
+			lhs: lhs.type_name(),
+			rhs: rhs.type_name(),
+			span,
+		};
+
 		Ok(match (lhs, rhs) {
 			(Value::Null, Value::Null) => Value::Bool(match operator {
 				InfixOperator::Equal => true,
 				InfixOperator::NotEqual => false,
-				operator => yeet!("Cannot perform operation `{operator:?}` on null"),
+				_ => yeet!(mismatch),
 			}),
-			(Value::Int(left), Value::Int(right)) => match operator {
-				InfixOperator::Add => Value::Int(left + right),
-				InfixOperator::Sub => Value::Int(left - right),
-				InfixOperator::Mul => Value::Int(left * right),
-				InfixOperator::Div => Value::Int(left / right),
-				InfixOperator::Modulo => Value::Int(left % right),
-				InfixOperator::Equal => Value::Bool(left == right),
-				InfixOperator::NotEqual => Value::Bool(left != right),
-				InfixOperator::GreaterThan => Value::Bool(left > right),
-				InfixOperator::LessThan => Value::Bool(left < right),
-				InfixOperator::GreaterThanOrEqual => Value::Bool(left >= right),
-				InfixOperator::LessThanOrEqual => Value::Bool(left <= right),
-			},
+			(Value::Int(left), Value::Int(right)) => {
+				Self::eval_int_infix(operator, left, right, span)?
+			}
+			(Value::BigInt(left), Value::BigInt(right)) => {
+				Self::eval_big_infix(operator, left, right, span)?
+			}
+			(Value::Int(left), Value::BigInt(right)) => {
+				Self::eval_big_infix(operator, left.into(), right, span)?
+			}
+			(Value::BigInt(left), Value::Int(right)) => {
+				Self::eval_big_infix(operator, left, right.into(), span)?
+			}
+			(Value::BigInt(left), Value::Float(right)) => {
+				Self::eval_float_infix(operator, Self::big_to_f64(&left), right)
+			}
+			(Value::Float(left), Value::BigInt(right)) => {
+				Self::eval_float_infix(operator, left, Self::big_to_f64(&right))
+			}
+			(Value::Float(left), Value::Float(right)) => {
+				Self::eval_float_infix(operator, left, right)
+			}
+			(Value::Int(left), Value::Float(right)) => {
+				Self::eval_float_infix(operator, left as f64, right)
+			}
+			(Value::Float(left), Value::Int(right)) => {
+				Self::eval_float_infix(operator, left, right as f64)
+			}
 			(Value::Bool(left), Value::Bool(right)) => Value::Bool(match operator {
 				InfixOperator::Equal => left == right,
 				InfixOperator::NotEqual => left != right,
-				operator => yeet!("Cannot perform operation `{operator:?}` on a boolean"),
+				_ => yeet!(mismatch),
+			}),
+			(Value::Function(left), Value::Function(right)) => Value::Bool(match operator {
+				InfixOperator::Equal => Rc::ptr_eq(&left, &right),
+				InfixOperator::NotEqual => !Rc::ptr_eq(&left, &right),
+				_ => yeet!(mismatch),
 			}),
 			(Value::String(left), Value::String(right)) => Value::String(match operator {
 				InfixOperator::Add => format!("{left}{right}"),
-				InfixOperator::Sub => yeet!("Cannot subtract one string from another"),
-				InfixOperator::Mul => yeet!("Cannot multiply strings"),
-				InfixOperator::Div => yeet!("Cannot divide strings"),
-				InfixOperator::Modulo => yeet!("Cannot modulo strings"),
+				InfixOperator::Sub => yeet!(mismatch),
+				InfixOperator::Mul => yeet!(mismatch),
+				InfixOperator::Div => yeet!(mismatch),
+				InfixOperator::Modulo => yeet!(mismatch),
 				InfixOperator::Equal => return Ok(Value::Bool(left == right)),
 				InfixOperator::NotEqual => return Ok(Value::Bool(left != right)),
 				InfixOperator::GreaterThan => return Ok(Value::Bool(left > right)),
@@ -279,47 +728,47 @@ impl Expression {
 			// FIXME: THIS IS HORRIBLE
 			(Value::String(left), Value::Int(right)) => {
 				if let Ok(left) = left.parse::<i64>() {
-					Self::eval_infix(operator, Value::Int(left), Value::Int(right))?
+					Self::eval_infix(operator, Value::Int(left), Value::Int(right), span)?
 				} else {
 					match operator {
 						InfixOperator::Add => Value::String(format!("{left}{right}")),
-						InfixOperator::Sub => yeet!("Cannot perform subtraction on strings"),
+						InfixOperator::Sub => yeet!(mismatch),
 						InfixOperator::Mul => match right {
 							0 => Value::String(String::new()),
 							n @ 1.. => Value::String(left.repeat(n as usize)),
-							_ => yeet!("Cannot multiply a string by a negative number"),
+							n => yeet!(RuntimeErrorKind::NegativeRepetition { count: n, span }),
 						},
-						InfixOperator::Div => yeet!("Cannot perform division on strings"),
-						InfixOperator::Modulo => yeet!("Cannot perform modulo on strings"),
+						InfixOperator::Div => yeet!(mismatch),
+						InfixOperator::Modulo => yeet!(mismatch),
 						InfixOperator::Equal
 						| InfixOperator::NotEqual
 						| InfixOperator::GreaterThan
 						| InfixOperator::LessThan
 						| InfixOperator::GreaterThanOrEqual
-						| InfixOperator::LessThanOrEqual => yeet!("Cannot compare string and number"),
+						| InfixOperator::LessThanOrEqual => yeet!(mismatch),
 					}
 				}
 			}
 			(Value::Int(left), Value::String(right)) => {
 				if let Ok(right) = right.parse::<i64>() {
-					Self::eval_infix(operator, Value::Int(right), Value::Int(left))?
+					Self::eval_infix(operator, Value::Int(left), Value::Int(right), span)?
 				} else {
 					match operator {
 						InfixOperator::Add => Value::String(format!("{right}{left}")),
-						InfixOperator::Sub => yeet!("Cannot perform subtraction on strings"),
+						InfixOperator::Sub => yeet!(mismatch),
 						InfixOperator::Mul => match left {
 							0 => Value::String(String::new()),
 							n @ 1.. => Value::String(right.repeat(n as usize)),
-							_ => yeet!("Cannot multiply a string by a negative number"),
+							n => yeet!(RuntimeErrorKind::NegativeRepetition { count: n, span }),
 						},
-						InfixOperator::Div => yeet!("Cannot perform division on strings"),
-						InfixOperator::Modulo => yeet!("Cannot perform modulo on strings"),
+						InfixOperator::Div => yeet!(mismatch),
+						InfixOperator::Modulo => yeet!(mismatch),
 						InfixOperator::Equal
 						| InfixOperator::NotEqual
 						| InfixOperator::GreaterThan
 						| InfixOperator::LessThan
 						| InfixOperator::GreaterThanOrEqual
-						| InfixOperator::LessThanOrEqual => yeet!("Cannot compare string and number"),
+						| InfixOperator::LessThanOrEqual => yeet!(mismatch),
 					}
 				}
 			}
@@ -328,31 +777,205 @@ impl Expression {
 				Value::Bool(match operator {
 					InfixOperator::Equal => int == bool,
 					InfixOperator::NotEqual => int != bool,
-					operator => {
-						yeet!(
-							"Cannot perform operation `{operator:?}` on a boolean and integer combo"
-						);
-					}
+					_ => yeet!(mismatch),
 				})
 			}
-			(lhs, rhs) => yeet!("Cannot evaluate `{lhs:?} {operator} {rhs:?}`"),
+			_ => yeet!(mismatch),
 		})
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn eval_array(mut array: Vec<Value>, mut idx: i64) -> Result<Value> {
-		Ok(match idx {
-			0.. if idx < array.len() as i64 => array.remove(idx as usize),
-			..=-1 => {
-				idx += array.len() as i64;
-				Self::eval_array(array, idx)?
+	fn eval_int_infix(operator: InfixOperator, left: i64, right: i64, span: Span) -> Result<Value> {
+		let result = match operator {
+			InfixOperator::Div | InfixOperator::Modulo if right == 0 => {
+				yeet!(RuntimeErrorKind::DivisionByZero { operator, lhs: left.to_string(), span })
 			}
+			InfixOperator::Add => left.checked_add(right),
+			InfixOperator::Sub => left.checked_sub(right),
+			InfixOperator::Mul => left.checked_mul(right),
+			InfixOperator::Div => left.checked_div(right),
+			// `i64::MIN % -1` is the only remainder that overflows, and it wraps to the correct `0`.
+			InfixOperator::Modulo => Some(left.wrapping_rem(right)),
+			InfixOperator::Equal => return Ok(Value::Bool(left == right)),
+			InfixOperator::NotEqual => return Ok(Value::Bool(left != right)),
+			InfixOperator::GreaterThan => return Ok(Value::Bool(left > right)),
+			InfixOperator::LessThan => return Ok(Value::Bool(left < right)),
+			InfixOperator::GreaterThanOrEqual => return Ok(Value::Bool(left >= right)),
+			InfixOperator::LessThanOrEqual => return Ok(Value::Bool(left <= right)),
+		};
+
+		match result {
+			Some(int) => Ok(Value::Int(int)),
+			// The result doesn't fit into an `i64`, so we redo it with big integers.
+			None => Self::eval_big_infix(operator, left.into(), right.into(), span),
+		}
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_big_infix(
+		operator: InfixOperator,
+		left: BigInt,
+		right: BigInt,
+		span: Span,
+	) -> Result<Value> {
+		Ok(match operator {
+			InfixOperator::Div | InfixOperator::Modulo if right.is_zero() => {
+				yeet!(RuntimeErrorKind::DivisionByZero { operator, lhs: left.to_string(), span })
+			}
+			InfixOperator::Add => Value::from(left + right),
+			InfixOperator::Sub => Value::from(left - right),
+			InfixOperator::Mul => Value::from(left * right),
+			InfixOperator::Div => Value::from(left / right),
+			InfixOperator::Modulo => Value::from(left % right),
+			InfixOperator::Equal => Value::Bool(left == right),
+			InfixOperator::NotEqual => Value::Bool(left != right),
+			InfixOperator::GreaterThan => Value::Bool(left > right),
+			InfixOperator::LessThan => Value::Bool(left < right),
+			InfixOperator::GreaterThanOrEqual => Value::Bool(left >= right),
+			InfixOperator::LessThanOrEqual => Value::Bool(left <= right),
+		})
+	}
+
+	/// Converts a big integer to the nearest float, which may be infinite.
+	fn big_to_f64(int: &BigInt) -> f64 {
+		int.to_f64().unwrap_or(f64::NAN)
+	}
+
+	/// Floats follow IEEE 754, so e.g. dividing by zero yields infinity instead of an error.
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_float_infix(operator: InfixOperator, left: f64, right: f64) -> Value {
+		match operator {
+			InfixOperator::Add => Value::Float(left + right),
+			InfixOperator::Sub => Value::Float(left - right),
+			InfixOperator::Mul => Value::Float(left * right),
+			InfixOperator::Div => Value::Float(left / right),
+			InfixOperator::Modulo => Value::Float(left % right),
+			InfixOperator::Equal => Value::Bool(left == right),
+			InfixOperator::NotEqual => Value::Bool(left != right),
+			InfixOperator::GreaterThan => Value::Bool(left > right),
+			InfixOperator::LessThan => Value::Bool(left < right),
+			InfixOperator::GreaterThanOrEqual => Value::Bool(left >= right),
+			InfixOperator::LessThanOrEqual => Value::Bool(left <= right),
+		}
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_index(lhs: Value, idx: Value, idx_span: Span, span: Span) -> Result<Value> {
+		Ok(match (lhs, idx) {
+			(Value::Array(array), Value::Int(idx)) => Self::eval_array(array, idx)?,
+			(Value::Map(map), key) => Self::eval_map(map, Self::eval_key(key, idx_span)?)?,
+			// Way out of bounds.
+			(Value::Array(_), Value::BigInt(_)) => Value::Null,
+
+			(lhs, idx) => yeet!(RuntimeErrorKind::InvalidIndex {
+				lhs: lhs.type_name(),
+				index: idx.type_name(),
+				span,
+			}),
+		})
+	}
+
+	/// Splits an assignment target like `a[0]["b"]` into the name of the variable and the indices
+	/// leading to the assigned value, evaluating every index exactly once.
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_target(
+		target: Expression,
+		environment: &Rc<Environment>,
+	) -> Flow<(String, Span, IndexPath)> {
+		match target.kind {
+			ExpressionKind::Identifier(name) => Ok((name, target.span, Vec::new())),
+			ExpressionKind::Index { lhs, idx } => {
+				let (name, name_span, mut path) = Self::eval_target(*lhs, environment)?;
+				let idx_span = idx.span;
+				path.push((idx.eval(Rc::clone(environment))?, idx_span, target.span));
+				Ok((name, name_span, path))
+			}
+			_ => unreachable!(
+				"the parser only accepts identifiers and indices as assignment targets"
+			),
+		}
+	}
+
+	/// Looks up the element at the end of `path` inside of `container`.
+	fn eval_path(container: Value, path: &[(Value, Span, Span)]) -> Result<Value> {
+		path.iter()
+			.try_fold(container, |current, (idx, idx_span, span)| {
+				Expression::eval_index(current, idx.clone(), *idx_span, *span)
+			})
+	}
+
+	/// Returns `container` with the element at the end of `path` replaced by `value`.
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn assign_path(container: Value, path: &[(Value, Span, Span)], value: Value) -> Result<Value> {
+		let Some(((idx, idx_span, span), rest)) = path.split_first() else {
+			return Ok(value);
+		};
+
+		let element = if rest.is_empty() {
+			value
+		} else {
+			let element = Self::eval_index(container.clone(), idx.clone(), *idx_span, *span)?;
+			Self::assign_path(element, rest, value)?
+		};
+
+		Self::assign_index(container, idx.clone(), element, *idx_span, *span)
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn assign_index(
+		container: Value,
+		idx: Value,
+		value: Value,
+		idx_span: Span,
+		span: Span,
+	) -> Result<Value> {
+		Ok(match (container, idx) {
+			(Value::Array(mut array), Value::Int(index)) => {
+				let length = array.len();
+				let position = if index < 0 { index + length as i64 } else { index };
+
+				match usize::try_from(position) {
+					Ok(position) if position < length => array[position] = value,
+					_ => yeet!(RuntimeErrorKind::IndexOutOfBounds { index, length, span }),
+				}
+
+				Value::Array(array)
+			}
+			(Value::Map(mut map), key) => {
+				map.insert(Self::eval_key(key, idx_span)?, value);
+				Value::Map(map)
+			}
+
+			(lhs, idx) => yeet!(RuntimeErrorKind::InvalidIndex {
+				lhs: lhs.type_name(),
+				index: idx.type_name(),
+				span,
+			}),
+		})
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn eval_array(mut array: Vec<Value>, idx: i64) -> Result<Value> {
+		// Negative indices count from the end, once.
+		let position = if idx < 0 { idx + array.len() as i64 } else { idx };
+
+		Ok(match usize::try_from(position) {
+			Ok(position) if position < array.len() => array.swap_remove(position),
 			_ => Value::Null,
 		})
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn eval_map(mut map: HashMap<String, Value>, key: String) -> Result<Value> {
+	fn eval_map(mut map: HashMap<MapKey, Value>, key: MapKey) -> Result<Value> {
 		Ok(map.remove(&key).unwrap_or(Value::Null))
 	}
+
+	/// Turns `key` into a map key, failing if it can't be hashed.
+	fn eval_key(key: Value, span: Span) -> Result<MapKey> {
+		let type_name = key.type_name();
+		match MapKey::new(key) {
+			Some(key) => Ok(key),
+			None => yeet!(RuntimeErrorKind::InvalidMapKey { key: type_name, span }),
+		}
+	}
 }
@@ -1,7 +1,9 @@
 use {
 	crate::{
-		ast::InfixOperator, eval::Environment, Eval, Expression, Parser, Statement, Tokenizer,
-		Value,
+		ast::InfixOperator,
+		eval::{Environment, Frame, RuntimeErrorKind},
+		Eval, Expression, ExpressionKind, Function, MapKey, Parser, Program, Statement,
+		StatementKind, Tokenizer, Value,
 	},
 	color_eyre::{eyre::bail as yeet, Result},
 	pretty_assertions::assert_eq,
@@ -26,6 +28,17 @@ fn eval_int_expression() -> Result<()> {
 		("3 * 3 * 3 + 10", 37),
 		("3 * (3 * 3) + 10", 37),
 		("(5 + 10 * 2 + 15 / 3) * 2 + -10", 50),
+		("9223372036854775807", i64::MAX),
+		("-9223372036854775808", i64::MIN),
+		("-9223372036854775807 - 1", i64::MIN),
+		("7 % 3", 1),
+		("-7 % 3", -1),
+		("1 + 7 % 4 * 2", 7),
+		("-9223372036854775808 % -1", 0),
+		// Numeric strings take part in arithmetic on either side.
+		("3 - \"10\"", -7),
+		("\"10\" - 3", 7),
+		("10 / \"2\"", 5),
 	];
 
 	for (input, value) in test_cases {
@@ -154,9 +167,9 @@ fn eval_map_expression() -> Result<()> {
 	assert_eq!(
 		evaluated,
 		Value::Map(HashMap::from_iter([
-			(String::from("one"), Value::Int(1)),
-			(String::from("two"), Value::Int(2)),
-			(String::from("three"), Value::Int(3)),
+			(MapKey::from("one"), Value::Int(1)),
+			(MapKey::from("two"), Value::Int(2)),
+			(MapKey::from("three"), Value::Int(3)),
 		]))
 	);
 
@@ -176,10 +189,18 @@ fn eval_index_expression() -> Result<()> {
 		("let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]", Value::Int(2)),
 		("[1, 2, 3][3]", Value::Null),
 		("[1, 2, 3][-1]", Value::Int(3)),
+		("[1, 2, 3][-3]", Value::Int(1)),
+		("[1][-5]", Value::Null),
+		("[][-1]", Value::Null),
 		("{\"foo\": 5}[\"foo\"]", Value::Int(5)),
 		("{\"foo\": 5}[\"bar\"]", Value::Null),
 		("let key = \"foo\"; {\"foo\": 5}[key]", Value::Int(5)),
 		("{}[\"foo\"]", Value::Null),
+		("{1: \"a\", true: \"b\"}[1]", Value::String("a".into())),
+		("{1: \"a\", true: \"b\"}[true]", Value::String("b".into())),
+		("{1: \"a\"}[\"1\"]", Value::Null),
+		("{[1, \"x\"]: 5}[[1, \"x\"]]", Value::Int(5)),
+		("{99999999999999999999: 5}[99999999999999999999]", Value::Int(5)),
 	];
 
 	for (input, expected) in test_cases {
@@ -230,11 +251,14 @@ fn eval_if_else_expression() -> Result<()> {
 #[test]
 fn eval_return_expression() -> Result<()> {
 	let test_cases = [
-		("return 10;", 10),
-		("return 10; 9;", 10),
-		("return 2 * 5; 9;", 10),
-		("9; return 2 * 5; 9;", 10),
-		("if (10 > 1) { if (10 > 1) { return 10; }; return 1; }", 10),
+		("fn() { return 10; }()", 10),
+		("fn() { return 10; 9; }()", 10),
+		("fn() { return 2 * 5; 9; }()", 10),
+		("fn() { 9; return 2 * 5; 9; }()", 10),
+		("fn() { if (10 > 1) { if (10 > 1) { return 10; }; return 1; } }()", 10),
+		("fn() { let x = if (true) { return 10; }; 1 }()", 10),
+		("fn() { let f = fn() { return 1; }; f(); 10 }()", 10),
+		("fn() { [1, 2, 3][fn() { return 1; }()] + 8 }()", 10),
 	];
 
 	for (input, value) in test_cases {
@@ -291,16 +315,18 @@ fn function_values() -> Result<()> {
 	assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
 
 	let evaluated = program.eval(Rc::clone(&environment))?;
-	let Value::Function { parameters, body, .. } = evaluated else {
+	let Value::Function(function) = evaluated else {
 		yeet!("Value was not a function but `{evaluated:?}`");
 	};
 
-	assert_eq!(parameters, vec![String::from("x")]);
-	assert_eq!(body, vec![Statement::Expression(Expression::Infix {
-		operator: InfixOperator::Add,
-		lhs: Box::new("x".into()),
-		rhs: Box::new(2.into())
-	})]);
+	assert_eq!(function.parameters, vec![String::from("x")]);
+	assert_eq!(function.body, vec![Statement::expression(Expression::from(
+		ExpressionKind::Infix {
+			operator: InfixOperator::Add,
+			lhs: Box::new("x".into()),
+			rhs: Box::new(2.into())
+		}
+	))]);
 
 	Ok(())
 }
@@ -356,3 +382,879 @@ fn closures() -> Result<()> {
 
 	Ok(())
 }
+
+#[test]
+fn closure_captures() -> Result<()> {
+	let test_cases = [
+		// Captured by reference, so every call sees the same `n`.
+		(
+			"let make = fn() { let n = 0; fn() { n += 1 } }; let c = make(); c(); c(); c()",
+			Value::Int(3),
+		),
+		(
+			"let make = fn() { let n = 0; fn() { n += 1 } }; let a = make(); let b = make(); a(); a(); b()",
+			Value::Int(1),
+		),
+		// Later bindings in the defining scope are visible, which makes recursion work.
+		(
+			"let f = fn() { let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(10) }; f()",
+			Value::Int(55),
+		),
+		("let f = fn() { x }; let x = 5; f()", Value::Int(5)),
+		// Arguments are evaluated where the call happens, not inside the callee.
+		("let id = fn(x) { x }; let f = fn() { let y = 3; id(y) }; f()", Value::Int(3)),
+		("let x = 1; let f = fn(x) { x }; f(2) + x", Value::Int(3)),
+		// Functions are only equal to themselves.
+		("let f = fn() { 1 }; let g = f; f == g", Value::Bool(true)),
+		("let f = fn() { 1 }; let g = fn() { 1 }; f == g", Value::Bool(false)),
+		("let make = fn() { fn() { 1 } }; make() == make()", Value::Bool(false)),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::default()))?;
+		assert_eq!(evaluated, expected, "Failed on `{input}`");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn tail_calls() -> Result<()> {
+	// Deep enough to overflow the stack if every call nested on top of the previous one.
+	let test_cases = [
+		(
+			"let count = fn(n) { if (n == 0) { 0 } else { count(n - 1) } }; count(20000)",
+			Value::Int(0),
+		),
+		(
+			"let sum = fn(n, acc) { if (n == 0) { return acc; } return sum(n - 1, acc + n); }; sum(20000, 0)",
+			Value::Int(200010000),
+		),
+		(
+			"let even = fn(n) { if (n == 0) { true } else { odd(n - 1) } }; let odd = fn(n) { if (n == 0) { false } else { even(n - 1) } }; even(20001)",
+			Value::Bool(false),
+		),
+		(
+			"let f = fn(n) { while (true) { if (n > 0) { return f(n - 1); } break; }; n }; f(20000)",
+			Value::Int(0),
+		),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::default()))?;
+		assert_eq!(evaluated, expected, "Failed on `{input}`");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn recursion_limit() -> Result<()> {
+	let count = "let count = fn(n) { if (n == 0) { 0 } else { 1 + count(n - 1) } };";
+	let environment = Rc::new(Environment::default());
+	environment.call_stack().set_max_depth(20);
+
+	let tokenizer = Tokenizer::new(format!("{count} count(19)").chars().collect());
+	let program = Parser::new(tokenizer)?.parse_program();
+	assert_eq!(program.eval(Rc::clone(&environment))?, Value::Int(19));
+
+	let tokenizer = Tokenizer::new("count(20)".chars().collect());
+	let program = Parser::new(tokenizer)?.parse_program();
+	let Err(error) = program.eval(Rc::clone(&environment)) else {
+		yeet!("`count(20)` should have overflowed the call stack");
+	};
+
+	let RuntimeErrorKind::StackOverflow { limit, span } = *error.kind else {
+		yeet!("Expected a stack overflow but got `{error}`");
+	};
+
+	let frames = error.backtrace;
+	assert_eq!(limit, 20);
+	assert_eq!(frames.len(), 20);
+	assert_eq!(frames[0].span.range(), 0..9);
+	assert!(
+		frames[1..]
+			.iter()
+			.all(|frame| frame.span.range() == (49..61))
+	);
+	assert_eq!(span.range(), 49..61);
+
+	// Calls that failed don't stay on the stack.
+	assert!(environment.call_stack().frames().is_empty());
+
+	// Tail calls replace the call they are made from, so they don't count.
+	let input = "let loop = fn(n) { if (n == 0) { 0 } else { loop(n - 1) } }; loop(1000)";
+	let tokenizer = Tokenizer::new(input.chars().collect());
+	let program = Parser::new(tokenizer)?.parse_program();
+	assert_eq!(program.eval(Rc::clone(&environment))?, Value::Int(0));
+
+	Ok(())
+}
+
+#[test]
+fn backtraces() -> Result<()> {
+	let input = r#"
+		let sub = fn(a, b) { a - b };
+		let apply = fn(f, x) { f(x, "one") };
+		let wrap = fn() { apply(sub, 1) * 2 };
+		wrap()
+	"#;
+
+	let tokenizer = Tokenizer::new(input.chars().collect());
+	let program = Parser::new(tokenizer)?.parse_program();
+	let error = program
+		.eval(Rc::new(Environment::default()))
+		.unwrap_err();
+
+	let frames = error
+		.backtrace
+		.iter()
+		.map(|Frame { name, arguments, .. }| (name.as_deref(), *arguments))
+		.collect::<Vec<_>>();
+
+	// `f(x, "one")` is a tail call, so it replaces `apply`.
+	assert_eq!(frames, [(Some("wrap"), 0), (Some("sub"), 2)]);
+
+	let input = "let f = fn() { fn(x) { x + null }(1) }; f()";
+	let tokenizer = Tokenizer::new(input.chars().collect());
+	let program = Parser::new(tokenizer)?.parse_program();
+	let error = program
+		.eval(Rc::new(Environment::default()))
+		.unwrap_err();
+
+	assert_eq!(error.backtrace.len(), 1);
+	assert_eq!(error.backtrace[0].name, None);
+	assert_eq!(error.backtrace[0].span.range(), 15..36);
+
+	Ok(())
+}
+
+#[test]
+fn try_catch() -> Result<()> {
+	let test_cases = [
+		("try { 1 } catch (e) { 2 }", Value::Int(1)),
+		("try { throw 1; 2 } catch (e) { 3 }", Value::Int(3)),
+		("try { throw 1 } catch (e) { e[\"value\"] }", Value::Int(1)),
+		("try { throw \"bad\" } catch (e) { e[\"message\"] }", Value::String("bad".into())),
+		("try { throw \"bad\" } catch (e) { e[\"kind\"] }", Value::String("Thrown".into())),
+		("try { 1 + null } catch (e) { e[\"kind\"] }", Value::String("TypeMismatch".into())),
+		("try { x } catch (e) { e[\"message\"] }", Value::String("Unknown identifier `x`".into())),
+		("try {\n  1 / 0\n} catch (e) { e[\"location\"] }", {
+			let location = [("line", 2), ("column", 3)]
+				.map(|(key, value)| (MapKey::from(key), Value::Int(value)));
+			Value::Map(HashMap::from(location))
+		}),
+		("try { throw {\"code\": 4} } catch (e) { e[\"code\"] }", Value::Int(4)),
+		// The error variable only lives inside of `catch`.
+		("let e = 1; try { throw 2 } catch (e) { e }; e", Value::Int(1)),
+		// Rethrowing keeps the original error.
+		(
+			"try { try { x } catch (e) { throw e } } catch (e) { e[\"kind\"] }",
+			Value::String("UnknownIdentifier".into()),
+		),
+		// Errors travel out of functions.
+		("let f = fn() { throw 5 }; try { f() } catch (e) { e[\"value\"] }", Value::Int(5)),
+		(
+			"let f = fn() { throw 5 }; let g = fn() { try { return f(); } catch (e) { 6 } }; g()",
+			Value::Int(6),
+		),
+		(
+			"let f = fn(n) { 1 + f(n) }; try { f(0) } catch (e) { e[\"kind\"] }",
+			Value::String("StackOverflow".into()),
+		),
+		// `finally` always runs, but doesn't change the result.
+		("let x = 0; try { 1 } catch (e) { 2 } finally { x = 3 }; x", Value::Int(3)),
+		("let x = 0; try { throw 1 } catch (e) { 2 } finally { x = 3 }; x", Value::Int(3)),
+		("try { 1 } catch (e) { 2 } finally { 3 }", Value::Int(1)),
+		(
+			"let x = 0; let f = fn() { try { return 1 } catch (e) { 2 } finally { x = 3 } }; f() + x",
+			Value::Int(4),
+		),
+		(
+			"let x = 0; try { try { throw 1 } catch (e) { throw 2 } finally { x = 3 } } catch (e) { x + e[\"value\"] }",
+			Value::Int(5),
+		),
+		(
+			"let f = fn() { try { return 1 } catch (e) { 2 } finally { return 3 } }; f()",
+			Value::Int(3),
+		),
+		(
+			"let n = 0; for (x in 0..3) { try { if (x == 1) { continue } } catch (e) { } finally { n += 1 } }; n",
+			Value::Int(3),
+		),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::default()))?;
+		assert_eq!(evaluated, expected, "Failed on `{input}`");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn error_values() -> Result<()> {
+	let test_cases = [
+		("int(\"42\")", Value::Int(42)),
+		("int(\" -7 \")", Value::Int(-7)),
+		("int(2.9)", Value::Int(2)),
+		("int(true)", Value::Int(1)),
+		("int(\"abc\")", Value::error("cannot parse `abc` as an int")),
+		("first([])", Value::Null),
+		("error(5)", Value::Error(Box::new(Value::Int(5)))),
+		("is_error(error(5))", Value::Bool(true)),
+		("is_error(5)", Value::Bool(false)),
+		("!error(5)", Value::Bool(true)),
+		// `?` passes errors on to the caller and leaves everything else alone.
+		("let f = fn(s) { int(s)? + 1 }; f(\"1\")", Value::Int(2)),
+		("let f = fn(s) { int(s)? + 1 }; f(\"x\")", Value::error("cannot parse `x` as an int")),
+		("let f = fn() { let x = null?; 1 }; f()", Value::Int(1)),
+		(
+			"let n = 0; let f = fn() { error(1)?; n = 1 }; let g = fn() { f()?; 2 }; [g(), n]",
+			Value::Array(vec![Value::Error(Box::new(Value::Int(1))), Value::Int(0)]),
+		),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::new_global()))?;
+		assert_eq!(evaluated, expected, "Failed on `{input}`");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn shadowed_builtins() -> Result<()> {
+	let test_cases = [
+		("let map = {\"a\": 1}; map[\"a\"]", Value::Int(1)),
+		("let len = 5; len = 6; len", Value::Int(6)),
+		("let f = fn() { let len = 1; len }; f() + len([1])", Value::Int(2)),
+		("let f = fn(keys) { keys }; f(3)", Value::Int(3)),
+		("let n = 0; for (range in [4]) { n = range }; n", Value::Int(4)),
+		("try { throw 5 } catch (error) { error[\"value\"] }", Value::Int(5)),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::new_global()))?;
+		assert_eq!(evaluated, expected, "Failed on `{input}`");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn higher_order_builtins() -> Result<()> {
+	let ints = |ints: &[i64]| Value::Array(ints.iter().copied().map(Value::Int).collect());
+	let test_cases = [
+		("map([1, 2, 3], fn(x) { x * 2 })", ints(&[2, 4, 6])),
+		("map([], fn(x) { x })", ints(&[])),
+		("map([[1], [2, 3]], first)", ints(&[1, 2])),
+		("filter([1, 2, 3, 4], fn(x) { x > 2 })", ints(&[3, 4])),
+		("reduce([1, 2, 3], 10, fn(acc, x) { acc + x })", Value::Int(16)),
+		("reduce([], 10, fn(acc, x) { acc + x })", Value::Int(10)),
+		("sort_by([3, 1, 2], fn(x) { x })", ints(&[1, 2, 3])),
+		("sort_by([3, 1, 2], fn(x) { -x })", ints(&[3, 2, 1])),
+		// Elements with the same key stay in order.
+		("sort_by([[1, 2], [0, 3], [1, 1]], first)", {
+			Value::Array(vec![ints(&[0, 3]), ints(&[1, 2]), ints(&[1, 1])])
+		}),
+		("let n = 0; each([1, 2, 3], fn(x) { n += x }); n", Value::Int(6)),
+		("any([1, 2, 3], fn(x) { x == 2 })", Value::Bool(true)),
+		("any([], fn(x) { true })", Value::Bool(false)),
+		("all([1, 2, 3], fn(x) { x > 0 })", Value::Bool(true)),
+		("all([1, 2, 3], fn(x) { x > 1 })", Value::Bool(false)),
+		// `any` and `all` stop as soon as they know the answer.
+		("let n = 0; any([1, 2, 3], fn(x) { n += 1; x == 1 }); n", Value::Int(1)),
+		("let n = 0; all([1, 2, 3], fn(x) { n += 1; x == 1 }); n", Value::Int(2)),
+		// Functions can return early and throw through builtins.
+		("map([1, 2], fn(x) { if (x == 1) { return 0 }; x })", ints(&[0, 2])),
+		("try { each([1], fn(x) { throw x + 1 }) } catch (e) { e[\"value\"] }", Value::Int(2)),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::new_global()))?;
+		assert_eq!(evaluated, expected, "Failed on `{input}`");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn array_builtins() -> Result<()> {
+	let ints = |ints: &[i64]| Value::Array(ints.iter().copied().map(Value::Int).collect());
+	let test_cases = [
+		("len([1, 2, 3])", Value::Int(3)),
+		("len(\"héllo\")", Value::Int(5)),
+		("len({\"a\": 1})", Value::Int(1)),
+		("len(1..=3)", Value::Int(3)),
+		("len(3..1)", Value::Int(0)),
+		("len(3..=2)", Value::Int(0)),
+		("len(0..=9223372036854775806)", Value::Int(i64::MAX)),
+		("len(-9223372036854775808..-1)", Value::Int(i64::MAX)),
+		("last([1, 2, 3])", Value::Int(3)),
+		("last([])", Value::Null),
+		("rest([1, 2, 3])", ints(&[2, 3])),
+		("rest([1])", ints(&[])),
+		("rest([])", Value::Null),
+		("let a = [1]; let b = push(a, 2); [a, b]", Value::Array(vec![ints(&[1]), ints(&[1, 2])])),
+		("let a = [1]; push_mut(a, 2)", Value::Int(2)),
+		("let a = [1]; push_mut(a, 2); push_mut(a, 3); a", ints(&[1, 2, 3])),
+		("let a = [[1], [2]]; push_mut(a[1], 3); a[1]", ints(&[2, 3])),
+		("let m = {\"k\": []}; push_mut(m[\"k\"], 1); m[\"k\"]", ints(&[1])),
+		("let a = [1, 2]; [pop(a), a]", Value::Array(vec![Value::Int(2), ints(&[1])])),
+		("let a = []; pop(a)", Value::Null),
+		("let a = [1]; let f = fn() { push_mut(a, 2) }; f(); a", ints(&[1, 2])),
+		// Through another builtin, the array is only changed for that call.
+		("let a = [[1]]; map(a, pop); a", Value::Array(vec![ints(&[1])])),
+		("slice([1, 2, 3, 4], 1, 3)", ints(&[2, 3])),
+		("slice([1, 2, 3, 4], 2)", ints(&[3, 4])),
+		("slice([1, 2, 3, 4], -2)", ints(&[3, 4])),
+		("slice([1, 2, 3, 4], 0, -1)", ints(&[1, 2, 3])),
+		("slice([1, 2], 2)", ints(&[])),
+		("concat([1], [], [2, 3])", ints(&[1, 2, 3])),
+		("reverse([1, 2, 3])", ints(&[3, 2, 1])),
+		("contains([1, \"a\", [2]], [2])", Value::Bool(true)),
+		("contains([1, 2], 3)", Value::Bool(false)),
+		("index_of([1, 2, 2], 2)", Value::Int(1)),
+		("index_of([1, 2], 3)", Value::Null),
+		("range(3)", ints(&[0, 1, 2])),
+		("range(2, 5)", ints(&[2, 3, 4])),
+		("range(0, 10, 3)", ints(&[0, 3, 6, 9])),
+		("range(3, 0, -1)", ints(&[3, 2, 1])),
+		("range(3, 0)", ints(&[])),
+		("range(0, 9223372036854775807, 4611686018427387904)", ints(&[0, 4611686018427387904])),
+		("range(-9223372036854775808, 9223372036854775807, 9223372036854775807)", {
+			ints(&[i64::MIN, -1, 9223372036854775806])
+		}),
+		("range(9223372036854775807, 9223372036854775805, -1)", {
+			ints(&[i64::MAX, 9223372036854775806])
+		}),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::new_global()))?;
+		assert_eq!(evaluated, expected, "Failed on `{input}`");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn map_builtins() -> Result<()> {
+	let ints = |ints: &[i64]| Value::Array(ints.iter().copied().map(Value::Int).collect());
+	let map = |entries: &[(i64, i64)]| {
+		Value::Map(
+			entries
+				.iter()
+				.map(|&(key, value)| (MapKey::Int(key), Value::Int(value)))
+				.collect(),
+		)
+	};
+
+	let test_cases = [
+		("keys({2: 0, 1: 0, 3: 0})", ints(&[1, 2, 3])),
+		("values({2: 20, 1: 10})", ints(&[10, 20])),
+		("entries({2: 20, 1: 10})", Value::Array(vec![ints(&[1, 10]), ints(&[2, 20])])),
+		("keys({})", ints(&[])),
+		("values({-99999999999999999999: 1, 5: 3, -1: 2, 99999999999999999999: 4})", {
+			ints(&[1, 2, 3, 4])
+		}),
+		(
+			"values({\"a\": 4, true: 3, 99999999999999999999: 2, [1]: 5, 1: 1})",
+			ints(&[1, 2, 3, 4, 5]),
+		),
+		("has({1: null}, 1)", Value::Bool(true)),
+		("has({1: 2}, 2)", Value::Bool(false)),
+		("has({[1, 2]: 3}, [1, 2])", Value::Bool(true)),
+		("let m = {}; insert(m, 1, 2); m", map(&[(1, 2)])),
+		("let m = {1: 2}; insert(m, 1, 3)", Value::Int(2)),
+		("let m = {}; insert(m, 1, 3)", Value::Null),
+		(
+			"let m = {1: 2, 3: 4}; [remove(m, 1), m]",
+			Value::Array(vec![Value::Int(2), map(&[(3, 4)])]),
+		),
+		("let m = {}; remove(m, 1)", Value::Null),
+		("let m = {\"a\": {}}; insert(m[\"a\"], 1, 2); m[\"a\"]", map(&[(1, 2)])),
+		("merge({1: 1, 2: 2}, {2: 3}, {4: 4})", map(&[(1, 1), (2, 3), (4, 4)])),
+		("from_entries([[1, 2], [3, 4], [1, 5]])", map(&[(1, 5), (3, 4)])),
+		("from_entries(entries({1: 2, 3: 4}))", map(&[(1, 2), (3, 4)])),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::new_global()))?;
+		assert_eq!(evaluated, expected, "Failed on `{input}`");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn assignments() -> Result<()> {
+	let test_cases = [
+		("let x = 1; x = 2; x", Value::Int(2)),
+		("let x = 1; x = 2", Value::Int(2)),
+		("let x = 1; x += 2; x *= 5; x -= 1; x /= 2; x", Value::Int(7)),
+		("let x = 17; x %= 5; x", Value::Int(2)),
+		("let a = [7]; a[0] %= 4; a[0]", Value::Int(3)),
+		("let s = \"a\"; s += \"b\"; s", Value::String("ab".into())),
+		("let a = 1; let b = 2; a = b = 3; a + b", Value::Int(6)),
+		("let x = 1; let f = fn() { x = 5 }; f(); x", Value::Int(5)),
+		("let x = 1; let f = fn() { let x = 2; x = 3 }; f(); x", Value::Int(1)),
+		("let n = 0; let inc = fn() { n += 1 }; inc(); inc(); n", Value::Int(2)),
+		(
+			"let a = [1, 2, 3]; a[0] = 10; a",
+			Value::Array(vec![Value::Int(10), Value::Int(2), Value::Int(3)]),
+		),
+		("let a = [1, 2, 3]; a[-1] += 1; a[2]", Value::Int(4)),
+		("let a = [[1], [2]]; a[1][0] = 5; a[1]", Value::Array(vec![Value::Int(5)])),
+		("let m = {\"k\": 1}; m[\"k\"] += 1; m[\"k\"]", Value::Int(2)),
+		(
+			"let m = {}; m[\"k\"] = [1]; m[\"k\"][0] = 2; m[\"k\"]",
+			Value::Array(vec![Value::Int(2)]),
+		),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::new_global()))?;
+		assert_eq!(evaluated, expected, "{input}");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn while_loops() -> Result<()> {
+	let test_cases = [
+		("let i = 0; while (i < 5) { i += 1 }; i", Value::Int(5)),
+		("while (false) { 1 }", Value::Null),
+		("let i = 0; while (true) { i += 1; if (i == 3) { break; } }; i", Value::Int(3)),
+		(
+			"let i = 0; let odd = 0; while (i < 10) { i += 1; if (i / 2 * 2 == i) { continue; } odd += 1; }; odd",
+			Value::Int(5),
+		),
+		(
+			"let i = 0; let n = 0; while (i < 3) { i += 1; let j = 0; while (true) { j += 1; n += 1; if (j == 2) { break; } } }; n",
+			Value::Int(6),
+		),
+		("let f = fn() { while (true) { return 7; } }; f()", Value::Int(7)),
+		("let i = 100000; while (i) { i -= 1 }; i", Value::Int(0)),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::default()))?;
+		assert_eq!(evaluated, expected, "{input}");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn for_loops() -> Result<()> {
+	let test_cases = [
+		("let sum = 0; for (x in [1, 2, 3]) { sum += x }; sum", Value::Int(6)),
+		("let sum = 0; for (i, x in [5, 5, 5]) { sum += i * x }; sum", Value::Int(15)),
+		("let s = \"\"; for (k in {\"b\": 1, \"a\": 2}) { s += k }; s", Value::String("ab".into())),
+		("let s = 0; for (k, v in {\"b\": 1, \"a\": 2}) { s = s * 10 + v }; s", Value::Int(21)),
+		("let s = 0; for (k in {3: 0, 1: 0, 2: 0}) { s = s * 10 + k }; s", Value::Int(123)),
+		("let s = \"\"; for (c in \"abc\") { s = c + s }; s", Value::String("cba".into())),
+		("let n = 0; for (i, c in \"héllo\") { n = i }; n", Value::Int(4)),
+		("let sum = 0; for (i in 0..5) { sum += i }; sum", Value::Int(10)),
+		("let sum = 0; for (i in 0..=5) { sum += i }; sum", Value::Int(15)),
+		("let sum = 0; for (i in 5..0) { sum += i }; sum", Value::Int(0)),
+		("let n = 0; for (i in 0..100) { if (i == 7) { break; } n = i }; n", Value::Int(6)),
+		("let n = 0; for (i in 0..10) { if (i > 2) { continue; } n += 1 }; n", Value::Int(3)),
+		("let f = fn() { for (i in 0..100) { if (i == 3) { return i; } } }; f()", Value::Int(3)),
+		("let x = 1; for (x in [2]) { }; x", Value::Int(1)),
+		("for (x in []) { 1 }", Value::Null),
+		("let f = 0; for (i in 0..3) { if (i == 1) { f = fn() { i } } }; f()", Value::Int(1)),
+		("1..3", Value::Range { start: 1, end: 3, inclusive: false }),
+		("let n = 2; -n..=n * 2", Value::Range { start: -2, end: 4, inclusive: true }),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::default()))?;
+		assert_eq!(evaluated, expected, "{input}");
+	}
+
+	assert_eq!(Value::Range { start: 0, end: 3, inclusive: true }.to_string(), "0..=3");
+
+	Ok(())
+}
+
+#[test]
+fn misplaced_control_flow() -> Result<()> {
+	// The parser rejects all of these, so the programs are put together by hand.
+	let test_cases = [
+		(Statement::r#return(ExpressionKind::Int(1)), "Cannot return outside of a function"),
+		(StatementKind::Break.into(), "`break` cannot be used outside of a loop"),
+		(StatementKind::Continue.into(), "`continue` cannot be used outside of a loop"),
+	];
+
+	for (statement, expected) in test_cases {
+		let program = Program { statements: vec![statement] };
+		let Err(error) = program.eval(Rc::new(Environment::default())) else {
+			yeet!("`{expected}` should have failed");
+		};
+
+		assert_eq!(error.to_string(), expected);
+	}
+
+	// A loop around the call doesn't count, the function body is where the search ends.
+	for (statement, keyword) in
+		[(StatementKind::Break, "break"), (StatementKind::Continue, "continue")]
+	{
+		let environment = Rc::new(Environment::default());
+		let function = Function {
+			name: None,
+			parameters: vec![],
+			body: vec![statement.into()],
+			environment: Rc::clone(&environment),
+		};
+		environment.set("f", Value::Function(Rc::new(function)));
+
+		let tokenizer = Tokenizer::new("for (x in [1]) { f() }".chars().collect());
+		let program = Parser::new(tokenizer)?.parse_program();
+		let Err(error) = program.eval(environment) else {
+			yeet!("`{keyword}` inside of `f` should have failed");
+		};
+
+		assert_eq!(error.to_string(), format!("`{keyword}` cannot be used outside of a loop"));
+	}
+
+	Ok(())
+}
+
+#[test]
+fn runtime_errors() -> Result<()> {
+	let test_cases = [
+		("foo", "Unknown identifier `foo`"),
+		("5 + true", "Cannot perform operation `Add` on a boolean and integer combo"),
+		("\"a\" - \"b\"", "Cannot subtract one string from another"),
+		("\"a\" * -1", "Cannot multiply a string by a negative number"),
+		("-true", "`bool` cannot be negated"),
+		("let five = 5; five()", "Expected function before call expression but got `int`"),
+		("fn(x) { x }(1, 2)", "Too many arguments! Expected 1 but got 2"),
+		("fn(x) { x }()", "Not enough arguments! Expected 1 but got 0"),
+		("if ([]) { 1 }", "Expected boolean expression inside conditional but got `array`"),
+		("{1.5: 2}", "Map keys must be ints, bools, strings or arrays of those, not `float`"),
+		("{[1, 2.5]: 2}", "Map keys must be ints, bools, strings or arrays of those, not `array`"),
+		("{}[1.5]", "Map keys must be ints, bools, strings or arrays of those, not `float`"),
+		(
+			"let m = {}; m[1.5] = 2",
+			"Map keys must be ints, bools, strings or arrays of those, not `float`",
+		),
+		("let m = {}; m[[0.5]] += 1", {
+			"Map keys must be ints, bools, strings or arrays of those, not `array`"
+		}),
+		("{}[null]", "Map keys must be ints, bools, strings or arrays of those, not `null`"),
+		("let m = {}; m[fn() {}] = 1", {
+			"Map keys must be ints, bools, strings or arrays of those, not `function`"
+		}),
+		("5[0]", "Invalid index operator access"),
+		("[] + {}", "Cannot evaluate `array + map`"),
+		("1 / 0", "Division by zero in `1 / 0`"),
+		("let x = 0; 5 / x", "Division by zero in `5 / 0`"),
+		("5 % 0", "Division by zero in `5 % 0`"),
+		("99999999999999999999 % 0", "Division by zero in `99999999999999999999 % 0`"),
+		("1 / \"0\"", "Division by zero in `1 / 0`"),
+		(
+			"while (\"yes\") { 1 }",
+			"Expected boolean expression inside conditional but got `string`",
+		),
+		("for (x in 5) { }", "Cannot iterate over `int`"),
+		("0..\"a\"", "Range bounds must be integers but got `int` and `string`"),
+		("x = 1", "Unknown identifier `x`"),
+		("x += 1", "Unknown identifier `x`"),
+		("print = 1", "Cannot override builtin value `print`"),
+		("let f = fn() { len = 1 }; f()", "Cannot override builtin value `len`"),
+		("let a = [1]; a[1] = 2", "Index 1 is out of bounds for an array of length 1"),
+		("let a = [1]; a[-2] = 2", "Index -2 is out of bounds for an array of length 1"),
+		("let a = 1; a[0] = 2", "Invalid index operator access"),
+		("let m = {}; m[\"a\"][\"b\"] = 1", "Invalid index operator access"),
+		("throw \"oops\"", "oops"),
+		("throw {\"message\": \"oops\"}", "oops"),
+		("throw 1", "1"),
+		("try { 1 } catch (e) { throw e[\"nope\"] }; throw 2", "2"),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let environment = Rc::new(Environment::new_global());
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let Err(error) = program.eval(Rc::clone(&environment)) else {
+			yeet!("`{input}` should have failed");
+		};
+
+		assert_eq!(error.to_string(), expected, "Failed on `{input}`");
+	}
+
+	let tokenizer = Tokenizer::new("let x = 1; x + y".chars().collect());
+	let mut parser = Parser::new(tokenizer)?;
+	let program = parser.parse_program();
+	let error = program
+		.eval(Rc::new(Environment::default()))
+		.unwrap_err();
+
+	assert!(
+		matches!(&*error.kind, RuntimeErrorKind::UnknownIdentifier { name, .. } if name == "y")
+	);
+	assert!(error.backtrace.is_empty());
+	assert_eq!(error.span().range(), 15..16);
+
+	// Map keys are pointed at the same way whether they are written, read or assigned.
+	for input in ["{1.5: 1}", "let m = {}; m[1.5]", "let m = {}; m[1.5] = 2"] {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let program = Parser::new(tokenizer)?.parse_program();
+		let error = program
+			.eval(Rc::new(Environment::default()))
+			.unwrap_err();
+		let start = input.find("1.5").unwrap();
+
+		assert!(matches!(&*error.kind, RuntimeErrorKind::InvalidMapKey { .. }), "{input}");
+		assert_eq!(error.span().range(), start..start + 3, "{input}");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn builtin_arguments() -> Result<()> {
+	let test_cases = [
+		("int()", "Not enough arguments! Expected 1 but got 0"),
+		("int(1, 2)", "Too many arguments! Expected 1 but got 2"),
+		("first()", "Not enough arguments! Expected at least 1 but got 0"),
+		("int([])", "Argument 1 of `int`: expected int or float or bool or string, got array"),
+		("first(1)", "Argument 1 of `first`: expected array, got int"),
+		("first([1], \"a\")", "Argument 2 of `first`: expected array, got string"),
+		("measure(\"a\", [], 3)", "Argument 3 of `measure`: expected string or array, got int"),
+		("map([1], 2)", "Argument 2 of `map`: expected function or builtin function, got int"),
+		("map([1], fn(a, b) { a })", "Not enough arguments! Expected 2 but got 1"),
+		("filter([1], fn(x) { \"yes\" })", {
+			"Expected boolean expression inside conditional but got `string`"
+		}),
+		("sort_by([1, \"a\"], fn(x) { x })", "Cannot compare string and number"),
+		("len(1)", "Argument 1 of `len`: expected array or map or string or range, got int"),
+		("push_mut([1], 2)", "`push_mut` changes its first argument, so it has to be a variable"),
+		("pop(first([[1]]))", "`pop` changes its first argument, so it has to be a variable"),
+		("pop(a)", "Unknown identifier `a`"),
+		("let a = 1; pop(a)", "Argument 1 of `pop`: expected array, got int"),
+		("let a = []; push_mut(a)", "Not enough arguments! Expected 2 but got 1"),
+		("slice([1, 2], 3)", "Index 3 is out of bounds for an array of length 2"),
+		("slice([1, 2], -3)", "Index -3 is out of bounds for an array of length 2"),
+		("slice([1, 2, 3], 2, 1)", "cannot slice from 2 to 1"),
+		("range(1, 2, 0)", "the step of `range` cannot be zero"),
+		("range(99999999999999999999)", "`99999999999999999999` is too large"),
+		("range(0, 9223372036854775807)", {
+			"`range` can create at most 16777216 numbers, but this would be 9223372036854775807"
+		}),
+		("range(0, -9223372036854775808, -1)", {
+			"`range` can create at most 16777216 numbers, but this would be 9223372036854775808"
+		}),
+		("len(0..=9223372036854775807)", {
+			"the length of `0..=9223372036854775807` does not fit into an int"
+		}),
+		("len(-1..9223372036854775807)", {
+			"the length of `-1..9223372036854775807` does not fit into an int"
+		}),
+		("keys([])", "Argument 1 of `keys`: expected map, got array"),
+		("has({}, 1.5)", "Map keys must be ints, bools, strings or arrays of those, not `float`"),
+		("insert({}, 1, 2)", "`insert` changes its first argument, so it has to be a variable"),
+		("let m = {}; remove(m, [null])", {
+			"Map keys must be ints, bools, strings or arrays of those, not `array`"
+		}),
+		("merge({}, [])", "Argument 2 of `merge`: expected map, got array"),
+		("from_entries([[1, 2], [3]])", "element 1 is not a `[key, value]` pair: [3]"),
+		("from_entries([1])", "element 0 is not a `[key, value]` pair: 1"),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+
+		let Err(error) = program.eval(Rc::new(Environment::new_global())) else {
+			yeet!("`{input}` should have failed");
+		};
+
+		assert_eq!(error.to_string(), expected, "Failed on `{input}`");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn eval_big_int_expression() -> Result<()> {
+	let big = |int: &str| -> Result<Value> { Ok(Value::BigInt(int.parse()?)) };
+
+	let test_cases = [
+		("9223372036854775807 + 1", big("9223372036854775808")?),
+		("-9223372036854775808 - 1", big("-9223372036854775809")?),
+		("4611686018427387904 * 4", big("18446744073709551616")?),
+		("-9223372036854775808 / -1", big("9223372036854775808")?),
+		("99999999999999999999 % 7", Value::Int(99999999999999999999_u128.rem_euclid(7) as i64)),
+		("let x = -9223372036854775808; -x", big("9223372036854775808")?),
+		("123456789012345678901234567890", big("123456789012345678901234567890")?),
+		("-123456789012345678901234567890 * 10", big("-1234567890123456789012345678900")?),
+		// Results that fit into an `i64` again are demoted.
+		("9223372036854775807 + 1 - 1", Value::Int(i64::MAX)),
+		("-9223372036854775808", Value::Int(i64::MIN)),
+		("99999999999999999999 / 99999999999999999999", Value::Int(1)),
+		("99999999999999999999 > 5", Value::Bool(true)),
+		("99999999999999999999 == 99999999999999999999", Value::Bool(true)),
+		("99999999999999999999 == 5", Value::Bool(false)),
+		("99999999999999999999 * 0.5", Value::Float(5e19)),
+		("[1, 2][99999999999999999999]", Value::Null),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::default()))?;
+		assert_eq!(evaluated, expected, "{input}");
+	}
+
+	assert_eq!(
+		big("-123456789012345678901234567890")?.to_string(),
+		"-123456789012345678901234567890"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn eval_float_expression() -> Result<()> {
+	let test_cases = [
+		("3.5", Value::Float(3.5)),
+		("-2.5", Value::Float(-2.5)),
+		("1.5 + 1.5", Value::Float(3.0)),
+		("1 + 0.5", Value::Float(1.5)),
+		("0.5 * 4", Value::Float(2.0)),
+		("7 / 2.0", Value::Float(3.5)),
+		("1 / 0.0", Value::Float(f64::INFINITY)),
+		("1.5 < 2", Value::Bool(true)),
+		("2.5 > 2", Value::Bool(true)),
+		("1 == 1.0", Value::Bool(true)),
+		("0.1 != 0.1", Value::Bool(false)),
+		("!0.0", Value::Bool(true)),
+		("if (0.5) { 1 } else { 2 }", Value::Int(1)),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+
+		let evaluated = program.eval(Rc::new(Environment::default()))?;
+		assert_eq!(evaluated, expected, "{input}");
+	}
+
+	// Floats are displayed in a way that reads back as the same float.
+	for float in [1.0, -0.5, 3.25, 1e100, 2.5e-8] {
+		let displayed = Value::Float(float).to_string();
+		let tokenizer = Tokenizer::new(displayed.chars().collect());
+		let program = Parser::new(tokenizer)?.parse_program();
+
+		assert_eq!(program.eval(Rc::new(Environment::default()))?, Value::Float(float));
+	}
+
+	// Infinity and NaN can't be written as literals, so they don't read back.
+	for (input, expected) in [("1.0 / 0.0", "inf"), ("-1.0 / 0.0", "-inf"), ("0.0 / 0.0", "NaN")] {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let program = Parser::new(tokenizer)?.parse_program();
+		let evaluated = program.eval(Rc::new(Environment::default()))?;
+
+		assert_eq!(evaluated.to_string(), expected, "{input}");
+	}
+
+	Ok(())
+}
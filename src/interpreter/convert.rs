@@ -0,0 +1,270 @@
+use {
+	crate::{eval::builtins::Arity, MapKey, RuntimeError, RuntimeErrorKind, Span, Value},
+	std::collections::HashMap,
+};
+
+/// A Rust type that Monkey values can be converted into.
+pub trait FromValue: Sized {
+	/// Describes the values that can be converted, for error messages, e.g. `array of int`.
+	fn expected() -> String;
+
+	/// Converts `value`, or returns `None` if it doesn't fit.
+	fn from_value(value: Value) -> Option<Self>;
+}
+
+/// A Rust type that can be converted into a Monkey value.
+pub trait IntoValue {
+	fn into_value(self) -> Value;
+}
+
+impl FromValue for Value {
+	fn expected() -> String {
+		String::from("any value")
+	}
+
+	fn from_value(value: Value) -> Option<Self> {
+		Some(value)
+	}
+}
+
+impl FromValue for i64 {
+	fn expected() -> String {
+		String::from("int")
+	}
+
+	fn from_value(value: Value) -> Option<Self> {
+		match value {
+			Value::Int(int) => Some(int),
+			_ => None,
+		}
+	}
+}
+
+impl FromValue for bool {
+	fn expected() -> String {
+		String::from("bool")
+	}
+
+	fn from_value(value: Value) -> Option<Self> {
+		match value {
+			Value::Bool(bool) => Some(bool),
+			_ => None,
+		}
+	}
+}
+
+impl FromValue for String {
+	fn expected() -> String {
+		String::from("string")
+	}
+
+	fn from_value(value: Value) -> Option<Self> {
+		match value {
+			Value::String(string) => Some(string),
+			_ => None,
+		}
+	}
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+	fn expected() -> String {
+		format!("array of {}", T::expected())
+	}
+
+	fn from_value(value: Value) -> Option<Self> {
+		match value {
+			Value::Array(array) => array.into_iter().map(T::from_value).collect(),
+			_ => None,
+		}
+	}
+}
+
+/// Only maps whose keys are all strings can be converted.
+impl<T: FromValue> FromValue for HashMap<String, T> {
+	fn expected() -> String {
+		format!("map of {}", T::expected())
+	}
+
+	fn from_value(value: Value) -> Option<Self> {
+		match value {
+			Value::Map(map) => map
+				.into_iter()
+				.map(|(key, value)| match key {
+					MapKey::String(key) => Some((key, T::from_value(value)?)),
+					_ => None,
+				})
+				.collect(),
+			_ => None,
+		}
+	}
+}
+
+/// `null` becomes `None`.
+impl<T: FromValue> FromValue for Option<T> {
+	fn expected() -> String {
+		format!("{} or null", T::expected())
+	}
+
+	fn from_value(value: Value) -> Option<Self> {
+		match value {
+			Value::Null => Some(None),
+			value => T::from_value(value).map(Some),
+		}
+	}
+}
+
+impl IntoValue for Value {
+	fn into_value(self) -> Value {
+		self
+	}
+}
+
+impl IntoValue for () {
+	fn into_value(self) -> Value {
+		Value::Null
+	}
+}
+
+impl IntoValue for i64 {
+	fn into_value(self) -> Value {
+		Value::Int(self)
+	}
+}
+
+impl IntoValue for bool {
+	fn into_value(self) -> Value {
+		Value::Bool(self)
+	}
+}
+
+impl IntoValue for String {
+	fn into_value(self) -> Value {
+		Value::String(self)
+	}
+}
+
+impl IntoValue for &str {
+	fn into_value(self) -> Value {
+		Value::String(self.to_owned())
+	}
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+	fn into_value(self) -> Value {
+		Value::Array(self.into_iter().map(T::into_value).collect())
+	}
+}
+
+impl<T: IntoValue> IntoValue for HashMap<String, T> {
+	fn into_value(self) -> Value {
+		Value::Map(
+			self.into_iter()
+				.map(|(key, value)| (MapKey::String(key), value.into_value()))
+				.collect(),
+		)
+	}
+}
+
+/// `None` becomes `null`.
+impl<T: IntoValue> IntoValue for Option<T> {
+	fn into_value(self) -> Value {
+		self.map_or(Value::Null, T::into_value)
+	}
+}
+
+/// The parameters of a native function, converted from the arguments it was called with.
+///
+/// Implemented for tuples of up to four [`FromValue`] types, which take exactly that many
+/// arguments, and for `Vec<T>`, which takes any number of them.
+pub trait FromArguments: Sized {
+	fn arity() -> Arity;
+
+	/// Converts `arguments`, which are already known to match the arity. `function` and `span`
+	/// are used to report arguments that don't fit.
+	fn from_arguments(
+		function: &str,
+		arguments: Vec<Value>,
+		span: Span,
+	) -> Result<Self, RuntimeError>;
+}
+
+/// Converts the argument at `position`, which starts at 1.
+fn argument<T: FromValue>(
+	function: &str,
+	position: usize,
+	value: Value,
+	span: Span,
+) -> Result<T, RuntimeError> {
+	// Native integers are `i64`s, so plain `int` would make the mismatch look like a match.
+	let got = match value {
+		Value::BigInt(_) => "int that doesn't fit into 64 bits",
+		_ => value.type_name(),
+	};
+
+	T::from_value(value).ok_or_else(|| {
+		RuntimeErrorKind::InvalidArgument {
+			function: function.to_owned(),
+			position,
+			expected: T::expected(),
+			got,
+			span,
+		}
+		.into()
+	})
+}
+
+impl<T: FromValue> FromArguments for Vec<T> {
+	fn arity() -> Arity {
+		Arity::Variadic { min: 0 }
+	}
+
+	fn from_arguments(
+		function: &str,
+		arguments: Vec<Value>,
+		span: Span,
+	) -> Result<Self, RuntimeError> {
+		arguments
+			.into_iter()
+			.zip(1..)
+			.map(|(value, position)| argument(function, position, value, span))
+			.collect()
+	}
+}
+
+impl FromArguments for () {
+	fn arity() -> Arity {
+		Arity::Fixed(0)
+	}
+
+	fn from_arguments(_: &str, _: Vec<Value>, _: Span) -> Result<Self, RuntimeError> {
+		Ok(())
+	}
+}
+
+macro_rules! tuple_arguments {
+	($count:literal; $($T:ident),+) => {
+		impl<$($T: FromValue),+> FromArguments for ($($T,)+) {
+			fn arity() -> Arity {
+				Arity::Fixed($count)
+			}
+
+			fn from_arguments(
+				function: &str,
+				arguments: Vec<Value>,
+				span: Span,
+			) -> Result<Self, RuntimeError> {
+				let mut arguments = arguments.into_iter().zip(1..);
+
+				Ok(($({
+					let (value, position) = arguments.next().expect("the arity was checked");
+					argument::<$T>(function, position, value, span)?
+				},)+))
+			}
+		}
+	};
+}
+
+tuple_arguments!(1; A);
+tuple_arguments!(2; A, B);
+tuple_arguments!(3; A, B, C);
+tuple_arguments!(4; A, B, C, D);
@@ -0,0 +1,197 @@
+//! Running Monkey code from Rust, with native functions provided by the host.
+//!
+//! ```
+//! use monkey::Interpreter;
+//!
+//! let interpreter = Interpreter::builder()
+//!     .with_function("add", |_, (a, b): (i64, i64)| Ok(a + b))
+//!     .build();
+//!
+//! assert_eq!(interpreter.run("add(1, 2)").unwrap(), monkey::Value::Int(3));
+//! ```
+//!
+//! Errors can be rendered against the code they came from with [`Interpreter::sources`] and
+//! [`crate::Diagnostic::render_sources`].
+
+#[cfg(test)]
+mod tests;
+
+use {
+	crate::{
+		diagnostic::Sources,
+		eval::{
+			builtins::{self, BuiltinFunction, Context},
+			Environment, DEFAULT_MAX_DEPTH,
+		},
+		Eval, ParseError, Parser, RuntimeError, Tokenizer, Value,
+	},
+	std::{
+		cell::{Ref, RefCell},
+		rc::Rc,
+	},
+};
+
+mod convert;
+pub use convert::{FromArguments, FromValue, IntoValue};
+
+mod native;
+pub use native::NativeFunction;
+
+/// A Monkey interpreter whose bindings persist from one [`Interpreter::run`] to the next.
+#[derive(Debug)]
+pub struct Interpreter {
+	environment: Rc<Environment>,
+	sources: RefCell<Sources>,
+}
+
+impl Interpreter {
+	/// An interpreter with the standard builtin functions.
+	pub fn new() -> Self {
+		Self::builder().build()
+	}
+
+	pub fn builder() -> InterpreterBuilder {
+		InterpreterBuilder::default()
+	}
+
+	/// The top-level scope that code runs in.
+	pub fn environment(&self) -> &Rc<Environment> {
+		&self.environment
+	}
+
+	/// The code passed to every [`Interpreter::run`] so far, which the spans of errors point into.
+	pub fn sources(&self) -> Ref<'_, Sources> {
+		self.sources.borrow()
+	}
+
+	/// Binds `name` to `value` in the top-level scope.
+	pub fn set(&self, name: impl Into<String>, value: impl IntoValue) {
+		self.environment.set(name, value.into_value());
+	}
+
+	/// Looks up `name` in the top-level scope. Returns `None` if it isn't bound, or if its value
+	/// can't be converted to `T`.
+	pub fn get<T: FromValue>(&self, name: &str) -> Option<T> {
+		self.environment.get(name).and_then(T::from_value)
+	}
+
+	/// Parses and evaluates `source`, returning the value of its last statement.
+	pub fn run(&self, source: &str) -> Result<Value, InterpreterError> {
+		// Functions defined here can fail in a later run, so the code has to stay around.
+		let id = self.sources.borrow_mut().add("<input>", source);
+		let tokenizer = Tokenizer::new(source.chars().collect()).with_source(id);
+		let mut parser = Parser::new(tokenizer).map_err(|error| vec![error])?;
+		let program = parser.parse_program();
+
+		if !parser.errors.is_empty() {
+			return Err(InterpreterError::Parse(parser.errors));
+		}
+
+		Ok(program.eval(Rc::clone(&self.environment))?)
+	}
+}
+
+impl Default for Interpreter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Sets up an [`Interpreter`]. Starts out with the standard builtin functions.
+#[derive(Debug)]
+pub struct InterpreterBuilder {
+	builtins: Vec<Rc<dyn BuiltinFunction>>,
+	max_depth: usize,
+}
+
+impl InterpreterBuilder {
+	/// Registers a Rust closure as a builtin function called `name`, replacing any builtin of the
+	/// same name.
+	///
+	/// The closure's parameters decide how many arguments the function takes and what types
+	/// they need to have, see [`FromArguments`]. Return errors created with [`Context::error`] to
+	/// make the call fail.
+	pub fn with_function<A, R, F>(self, name: impl Into<String>, function: F) -> Self
+	where
+		A: FromArguments + 'static,
+		R: IntoValue + 'static,
+		F: Fn(&Context, A) -> Result<R, RuntimeError> + 'static, {
+		self.with_builtin(NativeFunction::new(name, function))
+	}
+
+	/// Registers a builtin function, replacing any builtin of the same name.
+	pub fn with_builtin(mut self, builtin: impl BuiltinFunction + 'static) -> Self {
+		self.builtins.push(Rc::new(builtin));
+		self
+	}
+
+	/// How deeply calls may be nested before evaluation fails.
+	pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+		self.max_depth = max_depth;
+		self
+	}
+
+	pub fn build(self) -> Interpreter {
+		let environment = Environment::with_builtins(self.builtins);
+		environment
+			.call_stack()
+			.set_max_depth(self.max_depth);
+
+		Interpreter { environment: Rc::new(environment), sources: RefCell::default() }
+	}
+}
+
+impl Default for InterpreterBuilder {
+	fn default() -> Self {
+		Self { builtins: builtins::standard(), max_depth: DEFAULT_MAX_DEPTH }
+	}
+}
+
+/// Why [`Interpreter::run`] failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpreterError {
+	/// The code isn't a valid program. Nothing was evaluated.
+	Parse(Vec<ParseError>),
+
+	/// Evaluation stopped because of an error.
+	Runtime(RuntimeError),
+}
+
+impl From<Vec<ParseError>> for InterpreterError {
+	fn from(errors: Vec<ParseError>) -> Self {
+		Self::Parse(errors)
+	}
+}
+
+impl From<RuntimeError> for InterpreterError {
+	fn from(error: RuntimeError) -> Self {
+		Self::Runtime(error)
+	}
+}
+
+impl std::fmt::Display for InterpreterError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			InterpreterError::Parse(errors) => {
+				let errors = errors
+					.iter()
+					.map(|error| error.to_string())
+					.collect::<Vec<_>>();
+
+				write!(f, "{}", errors.join("\n"))
+			}
+			InterpreterError::Runtime(error) => write!(f, "{error}"),
+		}
+	}
+}
+
+impl std::error::Error for InterpreterError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			InterpreterError::Parse(errors) => errors
+				.first()
+				.map(|error| error as &dyn std::error::Error),
+			InterpreterError::Runtime(error) => Some(error),
+		}
+	}
+}
@@ -0,0 +1,57 @@
+use {
+	super::{FromArguments, IntoValue},
+	crate::{
+		eval::builtins::{Arity, BuiltinFunction, Context},
+		RuntimeError, Value,
+	},
+	std::marker::PhantomData,
+};
+
+/// A builtin function backed by a Rust closure, see [`super::InterpreterBuilder::with_function`].
+///
+/// The closure gets its parameters already converted to `A` and its result is converted back
+/// into a [`Value`], so it never has to deal with values of the wrong type.
+pub struct NativeFunction<A, R, F> {
+	name: String,
+	function: F,
+	signature: PhantomData<fn(A) -> R>,
+}
+
+impl<A, R, F> NativeFunction<A, R, F>
+where
+	A: FromArguments,
+	R: IntoValue,
+	F: Fn(&Context, A) -> Result<R, RuntimeError>,
+{
+	pub fn new(name: impl Into<String>, function: F) -> Self {
+		Self { name: name.into(), function, signature: PhantomData }
+	}
+}
+
+impl<A, R, F> std::fmt::Debug for NativeFunction<A, R, F> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("NativeFunction")
+			.field("name", &self.name)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<A, R, F> BuiltinFunction for NativeFunction<A, R, F>
+where
+	A: FromArguments,
+	R: IntoValue,
+	F: Fn(&Context, A) -> Result<R, RuntimeError>,
+{
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+
+	fn arity(&self) -> Arity {
+		A::arity()
+	}
+
+	fn call(&self, context: &Context, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let arguments = A::from_arguments(&self.name, arguments, context.span())?;
+		(self.function)(context, arguments).map(IntoValue::into_value)
+	}
+}
@@ -0,0 +1,131 @@
+use {
+	super::{Interpreter, InterpreterError},
+	crate::{Diagnostic, RuntimeErrorKind, Value},
+	color_eyre::{eyre::bail as yeet, Result},
+	pretty_assertions::assert_eq,
+	std::{cell::Cell, collections::HashMap, rc::Rc},
+};
+
+#[test]
+fn native_functions() -> Result<()> {
+	let calls = Rc::new(Cell::new(0));
+	let counter = Rc::clone(&calls);
+
+	let interpreter = Interpreter::builder()
+		.with_function("add", |_, (a, b): (i64, i64)| Ok(a + b))
+		.with_function("greet", |_, (name,): (Option<String>,)| {
+			Ok(format!("Hello, {}!", name.as_deref().unwrap_or("stranger")))
+		})
+		.with_function("sum", |_, numbers: Vec<i64>| Ok(numbers.iter().sum::<i64>()))
+		.with_function("count", move |_, ()| {
+			counter.set(counter.get() + 1);
+			Ok(())
+		})
+		.with_function("twice", |ctx, (function, value): (Value, Value)| {
+			let once = ctx.call(&function, vec![value])?;
+			ctx.call(&function, vec![once])
+		})
+		.build();
+
+	let test_cases = [
+		("add(1, 2)", Value::Int(3)),
+		("greet(\"Monkey\")", Value::String("Hello, Monkey!".into())),
+		("greet(null)", Value::String("Hello, stranger!".into())),
+		("sum()", Value::Int(0)),
+		("sum(1, 2, 3)", Value::Int(6)),
+		("count(); count()", Value::Null),
+		("twice(fn(x) { x * 2 }, 3)", Value::Int(12)),
+		// The standard builtins are still there.
+		("first([1])", Value::Int(1)),
+	];
+
+	for (input, expected) in test_cases {
+		assert_eq!(interpreter.run(input)?, expected, "Failed on `{input}`");
+	}
+
+	assert_eq!(calls.get(), 2);
+
+	Ok(())
+}
+
+#[test]
+fn native_function_errors() -> Result<()> {
+	let interpreter = Interpreter::builder()
+		.with_function("add", |_, (a, b): (i64, i64)| Ok(a + b))
+		.with_function("lengths", |_, (words,): (Vec<String>,)| {
+			Ok(words
+				.into_iter()
+				.map(|word| word.len() as i64)
+				.collect::<Vec<_>>())
+		})
+		.with_function("fail", |ctx, ()| -> Result<Value, _> { Err(ctx.error("nope")) })
+		.build();
+
+	let test_cases = [
+		("add(1)", "Not enough arguments! Expected 2 but got 1"),
+		("add(1, \"2\")", "Argument 2 of `add`: expected int, got string"),
+		("add(1, 99999999999999999999)", {
+			"Argument 2 of `add`: expected int, got int that doesn't fit into 64 bits"
+		}),
+		("lengths([\"a\", 1])", "Argument 1 of `lengths`: expected array of string, got array"),
+		("fail()", "nope"),
+		("add = 1", "Cannot override builtin value `add`"),
+	];
+
+	for (input, expected) in test_cases {
+		let Err(InterpreterError::Runtime(error)) = interpreter.run(input) else {
+			color_eyre::eyre::bail!("`{input}` should have failed at runtime");
+		};
+
+		assert_eq!(error.to_string(), expected, "Failed on `{input}`");
+	}
+
+	let Err(InterpreterError::Runtime(error)) = interpreter.run("fail()") else {
+		unreachable!();
+	};
+
+	assert!(matches!(*error.kind, RuntimeErrorKind::Builtin { .. }));
+	assert!(
+		matches!(interpreter.run("let = 1"), Err(InterpreterError::Parse(errors)) if !errors.is_empty())
+	);
+
+	Ok(())
+}
+
+#[test]
+fn host_values() -> Result<()> {
+	let interpreter = Interpreter::new();
+	interpreter.set("scores", HashMap::from([(String::from("a"), 1_i64), (String::from("b"), 2)]));
+	interpreter.run("let total = scores[\"a\"] + scores[\"b\"]; let names = [\"x\", \"y\"]")?;
+
+	assert_eq!(interpreter.get::<i64>("total"), Some(3));
+	assert_eq!(interpreter.get::<Vec<String>>("names"), Some(vec!["x".into(), "y".into()]));
+	assert_eq!(interpreter.get::<Vec<i64>>("names"), None);
+
+	interpreter.run("let numbered = {1: 2}")?;
+	assert_eq!(interpreter.get::<HashMap<String, i64>>("numbered"), None);
+	assert_eq!(interpreter.get::<Option<bool>>("nothing"), None);
+
+	interpreter.set("nothing", None::<bool>);
+	assert_eq!(interpreter.get::<Option<bool>>("nothing"), Some(None));
+
+	Ok(())
+}
+
+#[test]
+fn sources() -> Result<()> {
+	let interpreter = Interpreter::new();
+	interpreter.run("let half = fn(x) {\n\tx / 0\n}")?;
+
+	let Err(InterpreterError::Runtime(error)) = interpreter.run("1 + half(1)") else {
+		yeet!("dividing by zero should fail");
+	};
+
+	// The error points into the first input, although it was raised while running the second.
+	let rendered = Diagnostic::from(&error).render_sources(&interpreter.sources(), false);
+	assert!(rendered.contains("<input>:2:2"), "{rendered}");
+	assert!(rendered.contains("x / 0"), "{rendered}");
+	assert!(rendered.contains("called at <input>:1:5"), "{rendered}");
+
+	Ok(())
+}
@@ -1,26 +1,43 @@
 pub mod tracing;
 
+pub mod diagnostic;
+pub use diagnostic::Diagnostic;
+
+pub mod span;
+pub use span::{SourceId, Span};
+
 pub mod token;
-pub use token::Token;
+pub use token::{Token, TokenKind};
 
 pub mod tokenizer;
-pub use tokenizer::Tokenizer;
+pub use tokenizer::{TokenizeError, Tokenizer};
 
 pub mod ast;
-pub use ast::{Expression, Program, Statement};
+pub use ast::{Expression, ExpressionKind, Program, Statement, StatementKind};
 
 pub mod parser;
-pub use parser::Parser;
+pub use parser::{ParseError, Parser};
 
 pub mod value;
-pub use value::Value;
+pub use value::{Function, MapKey, Value};
 
 pub mod eval;
-pub use eval::Eval;
+pub use eval::{Eval, RuntimeError, RuntimeErrorKind};
+
+pub mod interpreter;
+pub use interpreter::{FromValue, Interpreter, InterpreterError, IntoValue};
 
 #[cfg(test)]
 mod test_setup;
 
+/// Returns early with the given error, converting it into the function's error type.
+#[macro_export]
+macro_rules! yeet {
+	($error:expr) => {
+		return Err($error.into())
+	};
+}
+
 #[macro_export]
 macro_rules! record {
 	($name:expr, $thing:expr) => {{
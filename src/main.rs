@@ -1,14 +1,28 @@
 use {
-	clap::{Parser as _, ValueEnum},
+	clap::{Parser as _, Subcommand, ValueEnum},
 	color_eyre::{eyre::Context, Result},
-	monkey::{eval::Environment, Eval, Parser, Token, Tokenizer},
+	monkey::{
+		diagnostic::Sources,
+		eval::{Environment, DEFAULT_MAX_DEPTH},
+		Diagnostic, Eval, ParseError, Parser, Program, TokenKind, Tokenizer, Value,
+	},
+	rustyline::error::ReadlineError,
 	std::{
-		io::{stdin, stdout, Write},
+		io::{stdout, IsTerminal},
+		ops::ControlFlow,
+		path::{Path, PathBuf},
+		process::ExitCode,
 		rc::Rc,
 		time::Instant,
 	},
 };
 
+mod editor;
+use editor::Editor;
+
+mod meta;
+use meta::{MetaCommand, COMMANDS};
+
 #[derive(Debug, clap::Parser)]
 pub struct Args {
 	/// Print debug information
@@ -18,6 +32,26 @@ pub struct Args {
 	#[arg(long)]
 	#[clap(default_value = "interpreter")]
 	mode: Mode,
+
+	/// How deeply function calls may be nested before evaluation fails
+	#[arg(long, global = true, default_value_t = DEFAULT_MAX_DEPTH)]
+	max_depth: usize,
+
+	#[command(subcommand)]
+	command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+	/// Run a Monkey source file
+	Run {
+		/// The file to run
+		path: PathBuf,
+
+		/// Arguments passed to the script, available as `args`
+		#[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+		args: Vec<String>,
+	},
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
@@ -35,135 +69,279 @@ impl std::fmt::Display for Mode {
 
 const PROMPT: &str = "=> ";
 
-fn main() -> Result<()> {
+/// The prompt shown while the user is still typing a multi-line construct.
+const CONTINUATION_PROMPT: &str = ".. ";
+
+/// The name we use for code typed into the REPL when reporting errors.
+const REPL: &str = "<repl>";
+
+fn main() -> Result<ExitCode> {
 	color_eyre::install()?;
 
-	let Args { debug, mode } = Args::parse();
+	let Args { debug, mode, max_depth, command } = Args::parse();
 	if debug {
 		monkey::tracing::init();
 	}
 
+	if let Some(Command::Run { path, args }) = command {
+		return run(&path, args, max_depth);
+	}
+
 	println!("Hello! This is the Monkey programming language!");
 	println!("Feel free to type in commands.");
-	println!("You can type `quit` to quit.");
+	println!("Type `:help` for a list of commands.");
 
-	match mode {
-		Mode::Tokenizer => tokenize(mode),
-		Mode::Parser => parse(mode),
-		Mode::Interpreter => interpret(mode),
-	}?;
+	repl(mode, max_depth)?;
 
-	Ok(())
+	Ok(ExitCode::SUCCESS)
 }
 
-fn tokenize(mode: Mode) -> Result<()> {
-	loop {
-		print!("[{mode:?}] {PROMPT}");
-		stdout()
-			.flush()
-			.context("Failed to flush STDOUT")?;
-
-		let mut input = String::new();
-		stdin()
-			.read_line(&mut input)
-			.context("Failed to read from STDIN")?;
-
-		if matches!(input.trim(), "quit" | "exit" | "bye") {
-			println!("Bye.");
-			break;
-		}
+fn run(path: &Path, args: Vec<String>, max_depth: usize) -> Result<ExitCode> {
+	let source = std::fs::read_to_string(path)
+		.with_context(|| format!("Failed to read `{}`", path.display()))?;
 
-		let input = input.chars().collect();
-		let mut tokenizer = Tokenizer::new(input);
-		let mut token = tokenizer.step()?;
+	let origin = path.display().to_string();
+	let mut sources = Sources::new();
+	let Some(program) = parse_source(&source, &origin, &mut sources) else {
+		return Ok(ExitCode::FAILURE);
+	};
 
-		let mut start = Instant::now();
-		while token != Token::Eof {
-			println!("{token:?} ({:?})", start.elapsed());
-			token = tokenizer.step()?;
-			start = Instant::now();
-		}
+	let environment = Rc::new(Environment::new_global());
+	environment.call_stack().set_max_depth(max_depth);
+
+	let args = args.into_iter().map(Value::String).collect();
+	environment.set("args", Value::Array(args));
+
+	if let Err(error) = program.eval(environment) {
+		report(&error, &sources);
+		return Ok(ExitCode::FAILURE);
 	}
 
-	Ok(())
+	Ok(ExitCode::SUCCESS)
 }
 
-fn parse(mode: Mode) -> Result<()> {
-	loop {
-		print!("[{mode:?}] {PROMPT}");
-		stdout()
-			.flush()
-			.context("Failed to flush STDOUT")?;
-
-		let mut input = String::new();
-		stdin()
-			.read_line(&mut input)
-			.context("Failed to read from STDIN")?;
-
-		if matches!(input.trim(), "quit" | "exit" | "bye") {
-			println!("Bye.");
+fn repl(mode: Mode, max_depth: usize) -> Result<()> {
+	let mut environment = Rc::new(Environment::new_global());
+	environment.call_stack().set_max_depth(max_depth);
+
+	let mut editor = Editor::new(Rc::clone(&environment))?;
+
+	// Every input is kept, since functions defined in it can fail long after it was read.
+	let mut sources = Sources::new();
+
+	while let Some(input) = read_input(&mut editor, mode)? {
+		let command = match MetaCommand::parse(&input) {
+			Ok(Some(command)) => command,
+			Ok(None) => {
+				match mode {
+					Mode::Tokenizer => print_tokens(&input),
+					Mode::Parser => print_ast(&input),
+					Mode::Interpreter => {
+						if let Some(value) = evaluate(&input, REPL, &environment, &mut sources) {
+							println!("{value}");
+						}
+					}
+				}
+
+				continue;
+			}
+			Err(message) => {
+				eprintln!("{message}");
+				continue;
+			}
+		};
+
+		if execute(command, &mut environment, &mut editor, &mut sources).is_break() {
 			break;
 		}
+	}
 
-		let input = input.chars().collect();
-		let tokenizer = Tokenizer::new(input);
-		let mut parser = Parser::new(tokenizer)?;
-		let program = parser.parse_program();
+	println!("Bye.");
 
-		if !parser.errors.is_empty() {
-			eprintln!("Failed to parse.\n{:?}", parser.errors);
-			continue;
-		}
+	Ok(())
+}
 
-		println!("{program:#?}");
+/// Runs a meta-command typed into the REPL. Breaks once the user wants to leave.
+fn execute(
+	command: MetaCommand,
+	environment: &mut Rc<Environment>,
+	editor: &mut Editor,
+	sources: &mut Sources,
+) -> ControlFlow<()> {
+	match command {
+		MetaCommand::Help => {
+			for (name, arguments, description) in COMMANDS {
+				println!("  :{name:<7}{arguments:<8}{description}");
+			}
+		}
+		MetaCommand::Quit => return ControlFlow::Break(()),
+		MetaCommand::Reset => {
+			let max_depth = environment.call_stack().max_depth();
+			*environment = Rc::new(Environment::new_global());
+			environment.call_stack().set_max_depth(max_depth);
+			editor.set_environment(Rc::clone(environment));
+			println!("Environment has been reset.");
+		}
+		MetaCommand::Env => {
+			for (name, value) in environment.bindings() {
+				if !matches!(value, Value::BuiltinFunction(_)) {
+					println!("{name} = {value}");
+				}
+			}
+		}
+		MetaCommand::Type(source) => {
+			if let Some(value) = evaluate(&source, REPL, environment, sources) {
+				println!("{}", value.type_name());
+			}
+		}
+		MetaCommand::Ast(source) => print_ast(&source),
+		MetaCommand::Tokens(source) => print_tokens(&source),
+		MetaCommand::Load(path) => match std::fs::read_to_string(&path) {
+			Ok(source) => {
+				let origin = path.display().to_string();
+				if evaluate(&source, &origin, environment, sources).is_some() {
+					println!("Loaded `{origin}`.");
+				}
+			}
+			Err(error) => eprintln!("Failed to read `{}`: {error}", path.display()),
+		},
+		MetaCommand::Time(source) => {
+			let start = Instant::now();
+			if let Some(value) = evaluate(&source, REPL, environment, sources) {
+				println!("{value}");
+			}
+			println!("Took {:?}", start.elapsed());
+		}
 	}
 
-	Ok(())
+	ControlFlow::Continue(())
 }
 
-fn interpret(mode: Mode) -> Result<()> {
-	let mut environment = Rc::new(Environment::new_global());
+fn print_tokens(source: &str) {
+	let mut sources = Sources::new();
+	let id = sources.add(REPL, source);
+	let mut tokenizer = Tokenizer::new(source.chars().collect()).with_source(id);
+	let mut start = Instant::now();
 
 	loop {
-		print!("[{mode:?}] {PROMPT}");
-		stdout()
-			.flush()
-			.context("Failed to flush STDOUT")?;
-
-		let mut input = String::new();
-		stdin()
-			.read_line(&mut input)
-			.context("Failed to read from STDIN")?;
-
-		match input.trim() {
-			"reset" => {
-				environment = Rc::new(Environment::new_global());
-				println!("Environment has been reset.");
-				continue;
-			}
-			"quit" | "exit" | "bye" => {
-				println!("Bye.");
+		let token = match tokenizer.step() {
+			Ok(token) if token.kind == TokenKind::Eof => break,
+			Ok(token) => token,
+			Err(error) => {
+				report(&error, &sources);
 				break;
 			}
+		};
+
+		println!("{:?} at {} ({:?})", token.kind, token.span, start.elapsed());
+		start = Instant::now();
+	}
+}
 
-			_ => {}
+fn print_ast(source: &str) {
+	if let Some(program) = parse_source(source, REPL, &mut Sources::new()) {
+		println!("{program:#?}");
+	}
+}
+
+/// Reads code from STDIN, prompting for more lines for as long as the input is incomplete.
+/// An empty line submits the input as-is, and Ctrl-C discards it.
+///
+/// Returns `None` once STDIN has been closed.
+fn read_input(editor: &mut Editor, mode: Mode) -> Result<Option<String>> {
+	let mut input = String::new();
+
+	loop {
+		let prompt = if input.is_empty() { PROMPT } else { CONTINUATION_PROMPT };
+		let line = match editor.read_line(&format!("[{mode:?}] {prompt}")) {
+			Ok(line) => line,
+			Err(ReadlineError::Interrupted) => {
+				input.clear();
+				continue;
+			}
+			Err(ReadlineError::Eof) if input.is_empty() => return Ok(None),
+			Err(ReadlineError::Eof) => break,
+			Err(error) => return Err(error).context("Failed to read from STDIN"),
 		};
 
-		let input = input.chars().collect();
-		let tokenizer = Tokenizer::new(input);
-		let mut parser = Parser::new(tokenizer)?;
-		let program = parser.parse_program();
+		let is_blank = line.trim().is_empty();
+		input.push_str(&line);
+		input.push('\n');
 
-		if !parser.errors.is_empty() {
-			eprintln!("Failed to parse.\n{:?}", parser.errors);
-			continue;
+		let source = MetaCommand::source(&input).unwrap_or_default();
+		if is_blank || !is_incomplete(source) {
+			break;
 		}
+	}
 
-		match program.eval(Rc::clone(&environment)) {
-			Ok(evaluated) => println!("{evaluated}"),
-			Err(error) => eprintln!("{error}"),
-		};
+	editor.add_history(&input);
+
+	Ok(Some(input))
+}
+
+/// Whether `source` is the beginning of a valid program that just hasn't been finished yet, e.g.
+/// because of an unclosed brace or a trailing operator.
+fn is_incomplete(source: &str) -> bool {
+	let tokenizer = Tokenizer::new(source.chars().collect());
+
+	match Parser::new(tokenizer) {
+		Ok(mut parser) => {
+			parser.parse_program();
+			parser
+				.errors
+				.iter()
+				.any(ParseError::is_incomplete)
+		}
+		Err(error) => error.is_incomplete(),
 	}
+}
 
-	Ok(())
+/// Parses and evaluates `source` in `environment`, reporting any errors to STDERR.
+fn evaluate(
+	source: &str,
+	origin: &str,
+	environment: &Rc<Environment>,
+	sources: &mut Sources,
+) -> Option<Value> {
+	let program = parse_source(source, origin, sources)?;
+
+	match program.eval(Rc::clone(environment)) {
+		Ok(value) => Some(value),
+		Err(error) => {
+			report(&error, sources);
+			None
+		}
+	}
+}
+
+/// Adds `source` to `sources` and parses it, reporting any errors to STDERR.
+fn parse_source(source: &str, origin: &str, sources: &mut Sources) -> Option<Program> {
+	let id = sources.add(origin, source);
+	let tokenizer = Tokenizer::new(source.chars().collect()).with_source(id);
+	let mut parser = match Parser::new(tokenizer) {
+		Ok(parser) => parser,
+		Err(error) => {
+			report(&error, sources);
+			return None;
+		}
+	};
+
+	let program = parser.parse_program();
+
+	if !parser.errors.is_empty() {
+		for error in &parser.errors {
+			report(error, sources);
+		}
+
+		return None;
+	}
+
+	Some(program)
+}
+
+/// Prints `error` to STDERR, pointing at the code in `sources` that caused it. Colours are used when
+/// STDOUT is a terminal.
+fn report(error: impl Into<Diagnostic>, sources: &Sources) {
+	let color = stdout().is_terminal();
+	eprintln!("{}", error.into().render_sources(sources, color));
 }
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+/// Commands understood by the REPL on top of Monkey code. They all start with a colon so they
+/// can't clash with identifiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetaCommand {
+	Help,
+	Quit,
+	Reset,
+	Env,
+	Type(String),
+	Ast(String),
+	Tokens(String),
+	Load(PathBuf),
+	Time(String),
+}
+
+/// Name, arguments and description of every command, as shown by `:help`.
+pub const COMMANDS: &[(&str, &str, &str)] = &[
+	("help", "", "Show this list"),
+	("quit", "", "Leave the REPL"),
+	("reset", "", "Forget every binding made in this session"),
+	("env", "", "List the bindings in the current environment"),
+	("type", "<expr>", "Show the type of the value `expr` evaluates to"),
+	("ast", "<expr>", "Show the syntax tree of `expr`"),
+	("tokens", "<expr>", "Show the tokens of `expr`"),
+	("load", "<file>", "Evaluate `file` into the current session"),
+	("time", "<expr>", "Evaluate `expr` and report how long that took"),
+];
+
+impl MetaCommand {
+	/// Parses `input` as a meta-command.
+	///
+	/// Returns `Ok(None)` if `input` isn't meant to be a meta-command at all, and an error message
+	/// if it is, but can't be understood.
+	pub fn parse(input: &str) -> Result<Option<Self>, String> {
+		let Some(input) = input.trim().strip_prefix(':') else {
+			return Ok(None);
+		};
+
+		let (name, argument) = input
+			.split_once(char::is_whitespace)
+			.map_or((input, ""), |(name, argument)| (name, argument.trim()));
+
+		let command = match name {
+			"help" | "h" | "?" => Self::Help,
+			"quit" | "q" | "exit" => Self::Quit,
+			"reset" => Self::Reset,
+			"env" => Self::Env,
+			"type" | "t" => Self::Type(Self::required(name, argument)?),
+			"ast" => Self::Ast(Self::required(name, argument)?),
+			"tokens" => Self::Tokens(Self::required(name, argument)?),
+			"load" | "l" => Self::Load(PathBuf::from(Self::required(name, argument)?)),
+			"time" => Self::Time(Self::required(name, argument)?),
+			_ => {
+				return Err(format!(
+					"Unknown command `:{name}`, type `:help` for a list of commands"
+				));
+			}
+		};
+
+		Ok(Some(command))
+	}
+
+	/// The Monkey code that `input` contains, which is the argument of a meta-command taking code
+	/// or `input` itself if it's not a meta-command.
+	///
+	/// Returns `None` for meta-commands that don't take any code.
+	pub fn source(input: &str) -> Option<&str> {
+		let Some(command) = input.trim_start().strip_prefix(':') else {
+			return Some(input);
+		};
+
+		let (name, argument) = command.split_once(char::is_whitespace)?;
+		matches!(name, "type" | "t" | "ast" | "tokens" | "time").then_some(argument)
+	}
+
+	fn required(name: &str, argument: &str) -> Result<String, String> {
+		if argument.is_empty() {
+			let (_, arguments, _) = COMMANDS
+				.iter()
+				.find(|(command, ..)| command.starts_with(name))
+				.copied()
+				.unwrap_or_default();
+
+			return Err(format!("Usage: :{name} {arguments}"));
+		}
+
+		Ok(argument.to_owned())
+	}
+}
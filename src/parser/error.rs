@@ -0,0 +1,98 @@
+use crate::{Expression, Span, Token, TokenKind, TokenizeError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+	/// The tokenizer failed to produce the next token.
+	Tokenize(TokenizeError),
+
+	/// We got a token that doesn't fit in the current position.
+	UnexpectedToken { got: Token, expected: Vec<String> },
+
+	/// There is no expression that can start with this token.
+	NoPrefixParser { token: Token },
+
+	/// The token cannot be used as a prefix operator.
+	InvalidPrefixOperator { token: Token },
+
+	/// The expression before `(` cannot be called.
+	InvalidCallee { function: Box<Expression> },
+
+	/// The expression before `=` (or `+=` etc.) cannot be assigned to.
+	InvalidAssignmentTarget { target: Box<Expression> },
+
+	/// `break` or `continue` was used outside of a loop.
+	LoopControlOutsideLoop { token: Token },
+
+	/// `return` or `?` was used outside of a function.
+	ReturnOutsideFunction { token: Token },
+}
+
+impl ParseError {
+	/// Whether the error was caused by the input ending too early, meaning that more input could
+	/// turn it into a valid program.
+	pub fn is_incomplete(&self) -> bool {
+		match self {
+			ParseError::Tokenize(TokenizeError::UnterminatedString { .. }) => true,
+			ParseError::UnexpectedToken { got: token, .. }
+			| ParseError::NoPrefixParser { token } => token.kind == TokenKind::Eof,
+			_ => false,
+		}
+	}
+
+	pub fn span(&self) -> Span {
+		match self {
+			ParseError::Tokenize(error) => error.span(),
+			ParseError::UnexpectedToken { got: token, .. }
+			| ParseError::NoPrefixParser { token }
+			| ParseError::InvalidPrefixOperator { token }
+			| ParseError::LoopControlOutsideLoop { token }
+			| ParseError::ReturnOutsideFunction { token } => token.span,
+			ParseError::InvalidCallee { function } => function.span,
+			ParseError::InvalidAssignmentTarget { target } => target.span,
+		}
+	}
+}
+
+impl From<TokenizeError> for ParseError {
+	fn from(error: TokenizeError) -> Self {
+		Self::Tokenize(error)
+	}
+}
+
+impl std::fmt::Display for ParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ParseError::Tokenize(error) => write!(f, "{error}"),
+			ParseError::UnexpectedToken { got, expected } => {
+				write!(f, "Unexpected token. Got: {:?}, Wanted: {}", got.kind, expected.join(" | "))
+			}
+			ParseError::NoPrefixParser { token } => {
+				write!(f, "We don't know how to parse `{:?}`", token.kind)
+			}
+			ParseError::InvalidPrefixOperator { token } => {
+				write!(f, "`{:?}` is not a valid prefix operator", token.kind)
+			}
+			ParseError::InvalidCallee { function } => {
+				write!(f, "Invalid function expression `{function}`")
+			}
+			ParseError::InvalidAssignmentTarget { target } => {
+				write!(f, "Cannot assign to `{target}`")
+			}
+			ParseError::LoopControlOutsideLoop { token } => {
+				write!(f, "`{token}` cannot be used outside of a loop")
+			}
+			ParseError::ReturnOutsideFunction { token } => {
+				write!(f, "`{token}` cannot be used outside of a function")
+			}
+		}
+	}
+}
+
+impl std::error::Error for ParseError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			ParseError::Tokenize(error) => Some(error),
+			_ => None,
+		}
+	}
+}
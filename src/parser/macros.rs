@@ -1,36 +1,44 @@
 macro_rules! assert_token {
-	(current, $parser:expr, $token:pat $(=> $ret:block)?) => {
-		if let $token = &$parser.current_token {
+	(current, $parser:expr, TokenKind::$kind:ident $(($binding:ident))? $(=> $ret:block)?) => {
+		if let $crate::TokenKind::$kind $(($binding))? = &$parser.current_token.kind {
 			$( $ret )?
 		} else {
-			assert_token!(__error, $parser, $token);
+			assert_token!(__error, $parser.current_token, $kind $(($binding))?);
 		}
 	};
 
-	(peek, $parser:expr, $token:pat) => {{
-		if let $token = &$parser.peek_token {
+	(peek, $parser:expr, TokenKind::$kind:ident $(($binding:ident))?) => {{
+		if let $crate::TokenKind::$kind $(($binding))? = &$parser.peek_token.kind {
 			$parser.step()?;
 		} else {
-			assert_token!(__error, $parser, $token);
+			assert_token!(__error, $parser.peek_token, $kind $(($binding))?);
 		}
 	}};
 
-	(peek, $parser:expr, $token:pat => $ret:block) => {{
-		if let $token = &$parser.peek_token {
+	(peek, $parser:expr, TokenKind::$kind:ident $(($binding:ident))? => $ret:block) => {{
+		if let $crate::TokenKind::$kind $(($binding))? = &$parser.peek_token.kind {
 			let ret = $ret;
 			$parser.step()?;
 			ret
 		} else {
-			assert_token!(__error, $parser, $token);
+			assert_token!(__error, $parser.peek_token, $kind $(($binding))?);
 		}
 	}};
 
-	(__error, $parser:expr, $token:pat) => {
-		::color_eyre::eyre::bail!(
-			"Unexpected token. Got: {:?}, Wanted: {}",
-			$parser.peek_token,
-			stringify!($token),
-		)
+	// Tokens with a payload are described by their kind, so any payload will do.
+	(__error, $got:expr, $kind:ident($binding:ident)) => {
+		assert_token!(__error, $got, $crate::TokenKind::$kind(Default::default()))
+	};
+
+	(__error, $got:expr, $kind:ident) => {
+		assert_token!(__error, $got, $crate::TokenKind::$kind)
+	};
+
+	(__error, $got:expr, $expected:expr) => {
+		$crate::yeet!($crate::parser::ParseError::UnexpectedToken {
+			got: $got.clone(),
+			expected: vec![$expected.describe()],
+		})
 	};
 }
 
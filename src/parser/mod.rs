@@ -4,9 +4,8 @@ mod tests;
 use {
 	crate::{
 		ast::{InfixOperator, PrefixOperator, Program},
-		Expression, Statement, Token, Tokenizer,
+		yeet, Expression, ExpressionKind, Statement, StatementKind, Token, TokenKind, Tokenizer,
 	},
-	color_eyre::{eyre::bail as yeet, Result},
 	macros::assert_token,
 	std::result::Result as StdResult,
 };
@@ -15,11 +14,22 @@ mod macros;
 pub mod precedence;
 pub use precedence::Precedence;
 
+mod error;
+pub use error::ParseError;
+
+type Result<T, E = ParseError> = StdResult<T, E>;
+
 pub struct Parser {
 	tokenizer: Tokenizer,
 	current_token: Token,
 	peek_token: Token,
-	pub errors: Vec<color_eyre::Report>,
+	pub errors: Vec<ParseError>,
+
+	/// How many loops we are currently inside of, within the innermost function.
+	loop_depth: usize,
+
+	/// How many function bodies we are currently inside of.
+	function_depth: usize,
 }
 
 impl Parser {
@@ -28,14 +38,14 @@ impl Parser {
 		let current_token = tokenizer.step()?;
 		let peek_token = tokenizer.step()?;
 		let errors = Vec::new();
-		Ok(Self { tokenizer, current_token, peek_token, errors })
+		Ok(Self { tokenizer, current_token, peek_token, errors, loop_depth: 0, function_depth: 0 })
 	}
 
 	#[tracing::instrument(level = "TRACE", ret)]
 	pub fn parse_program(&mut self) -> Program {
 		let mut program = Program::default();
 
-		while self.current_token != Token::Eof {
+		while self.current_token.kind != TokenKind::Eof {
 			match self.parse_statement() {
 				Ok(statement) => program.statements.push(statement),
 				Err(error) => self.errors.push(error),
@@ -59,38 +69,82 @@ impl Parser {
 
 	#[tracing::instrument(level = "INFO", ret)]
 	fn parse_statement(&mut self) -> Result<Statement> {
-		match &self.current_token {
-			Token::Let => self.parse_let(),
-			Token::Return => self.parse_return(),
-			_ => self.parse_expression_statement(),
-		}
+		let start = self.current_token.span;
+		let kind = match &self.current_token.kind {
+			TokenKind::Let => self.parse_let()?,
+			TokenKind::Return => self.parse_return()?,
+			TokenKind::Throw => self.parse_throw()?,
+			TokenKind::Break | TokenKind::Continue => self.parse_loop_control()?,
+			_ => self.parse_expression_statement()?,
+		};
+
+		Ok(Statement::new(kind, start.to(self.current_token.span)))
 	}
 
 	#[tracing::instrument(level = "INFO", ret)]
-	fn parse_let(&mut self) -> Result<Statement> {
-		let name = assert_token!(peek, self, Token::Ident(ident) => {
+	fn parse_let(&mut self) -> Result<StatementKind> {
+		let name = assert_token!(peek, self, TokenKind::Ident(ident) => {
 			ident.to_owned()
 		});
 
-		assert_token!(peek, self, Token::Assign);
+		assert_token!(peek, self, TokenKind::Assign);
 		self.step()?;
 
 		let value = self.parse_expression(Precedence::Lowest)?;
 
-		if self.peek_token == Token::Semicolon {
+		if self.peek_token.kind == TokenKind::Semicolon {
+			self.step()?;
+		}
+
+		Ok(StatementKind::Let { name, value })
+	}
+
+	#[tracing::instrument(level = "INFO", ret)]
+	fn parse_return(&mut self) -> Result<StatementKind> {
+		if self.function_depth == 0 {
+			let token = self.current_token.clone();
+			self.errors
+				.push(ParseError::ReturnOutsideFunction { token });
+		}
+
+		self.step()?;
+		let value = self.parse_expression(Precedence::Lowest)?;
+		let statement = StatementKind::Return { value };
+
+		if self.peek_token.kind == TokenKind::Semicolon {
 			self.step()?;
 		}
 
-		Ok(Statement::Let { name, value })
+		Ok(statement)
 	}
 
 	#[tracing::instrument(level = "INFO", ret)]
-	fn parse_return(&mut self) -> Result<Statement> {
+	fn parse_throw(&mut self) -> Result<StatementKind> {
 		self.step()?;
 		let value = self.parse_expression(Precedence::Lowest)?;
-		let statement = Statement::Return { value };
 
-		if self.peek_token == Token::Semicolon {
+		if self.peek_token.kind == TokenKind::Semicolon {
+			self.step()?;
+		}
+
+		Ok(StatementKind::Throw { value })
+	}
+
+	#[tracing::instrument(level = "INFO", ret)]
+	fn parse_loop_control(&mut self) -> Result<StatementKind> {
+		// The statement itself is fine, so we keep going instead of tripping over what follows.
+		if self.loop_depth == 0 {
+			let token = self.current_token.clone();
+			self.errors
+				.push(ParseError::LoopControlOutsideLoop { token });
+		}
+
+		let statement = match self.current_token.kind {
+			TokenKind::Break => StatementKind::Break,
+			_ => StatementKind::Continue,
+		};
+
+		if self.peek_token.kind == TokenKind::Semicolon {
 			self.step()?;
 		}
 
@@ -101,7 +155,9 @@ impl Parser {
 	fn parse_expression(&mut self, precedence: Precedence) -> Result<Expression> {
 		let mut prefix = self.parse_prefix()?;
 
-		while self.peek_token != Token::Semicolon && precedence < self.peek_token.precedence() {
+		while self.peek_token.kind != TokenKind::Semicolon
+			&& precedence < self.peek_token.precedence()
+		{
 			self.step()?;
 			match self.parse_infix(prefix)? {
 				Ok(expression) => prefix = expression,
@@ -113,135 +169,221 @@ impl Parser {
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn parse_expression_statement(&mut self) -> Result<Statement> {
+	fn parse_expression_statement(&mut self) -> Result<StatementKind> {
 		let expression = self.parse_expression(Precedence::Lowest)?;
 
-		if self.peek_token == Token::Semicolon {
+		if self.peek_token.kind == TokenKind::Semicolon {
 			self.step()?;
 		}
 
-		Ok(Statement::Expression(expression))
+		Ok(StatementKind::Expression(expression))
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
 	pub fn parse_prefix(&mut self) -> Result<Expression> {
-		let expression = match &self.current_token {
-			Token::Minus | Token::Bang => self.parse_prefix_expression()?,
-			Token::Int(int) => Expression::Int(*int),
-			Token::Ident(identifier) => Expression::Identifier(identifier.to_owned()),
-			Token::String(string) => Expression::String(string.to_owned()),
-			Token::True => Expression::Bool(true),
-			Token::False => Expression::Bool(false),
-			Token::LeftParen => self.parse_grouped_expression()?,
-			Token::LeftBracket => self.parse_array_expression()?,
-			Token::LeftBrace => self.parse_map_expression()?,
-			Token::If => self.parse_if_expression()?,
-			Token::Function => self.parse_function()?,
-			token => yeet!("We don't know how to parse `{token:?}`"),
+		let start = self.current_token.span;
+		let kind = match &self.current_token.kind {
+			TokenKind::Minus | TokenKind::Bang => self.parse_prefix_expression()?,
+			TokenKind::Int(int) => ExpressionKind::Int(*int),
+			TokenKind::BigInt(int) => ExpressionKind::BigInt(int.clone()),
+			TokenKind::Float(float) => ExpressionKind::Float(*float),
+			TokenKind::Ident(identifier) => ExpressionKind::Identifier(identifier.to_owned()),
+			TokenKind::String(string) => ExpressionKind::String(string.to_owned()),
+			TokenKind::True => ExpressionKind::Bool(true),
+			TokenKind::False => ExpressionKind::Bool(false),
+			TokenKind::LeftParen => self.parse_grouped_expression()?,
+			TokenKind::LeftBracket => self.parse_array_expression()?,
+			TokenKind::LeftBrace => self.parse_map_expression()?,
+			TokenKind::If => self.parse_if_expression()?,
+			TokenKind::While => self.parse_while_expression()?,
+			TokenKind::For => self.parse_for_expression()?,
+			TokenKind::Try => self.parse_try_expression()?,
+			TokenKind::Function => self.parse_function()?,
+			_ => yeet!(ParseError::NoPrefixParser { token: self.current_token.clone() }),
 		};
 
-		Ok(expression)
+		Ok(Expression::new(kind, start.to(self.current_token.span)))
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn parse_prefix_expression(&mut self) -> Result<Expression> {
+	fn parse_prefix_expression(&mut self) -> Result<ExpressionKind> {
 		let operator = PrefixOperator::try_from(&self.current_token)?;
 		self.step()?;
 		let rhs = self.parse_expression(Precedence::Prefix)?;
 
-		Ok(Expression::Prefix { operator, rhs: Box::new(rhs) })
+		Ok(ExpressionKind::Prefix { operator, rhs: Box::new(rhs) })
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn parse_grouped_expression(&mut self) -> Result<Expression> {
+	fn parse_grouped_expression(&mut self) -> Result<ExpressionKind> {
 		self.step()?;
 		let expression = self.parse_expression(Precedence::Lowest)?;
-		assert_token!(peek, self, Token::RightParen);
-		Ok(expression)
+		assert_token!(peek, self, TokenKind::RightParen);
+		Ok(expression.kind)
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn parse_array_expression(&mut self) -> Result<Expression> {
+	fn parse_array_expression(&mut self) -> Result<ExpressionKind> {
 		let mut array = Vec::new();
 
 		self.step()?;
-		if self.current_token == Token::RightBracket {
-			return Ok(Expression::Array(array));
+		if self.current_token.kind == TokenKind::RightBracket {
+			return Ok(ExpressionKind::Array(array));
 		}
 
 		array.push(self.parse_expression(Precedence::Lowest)?);
-		while self.peek_token == Token::Comma {
+		while self.peek_token.kind == TokenKind::Comma {
 			self.step()?;
 			self.step()?;
 			array.push(self.parse_expression(Precedence::Lowest)?);
 		}
 
-		assert_token!(peek, self, Token::RightBracket);
+		assert_token!(peek, self, TokenKind::RightBracket);
 
-		Ok(Expression::Array(array))
+		Ok(ExpressionKind::Array(array))
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn parse_map_expression(&mut self) -> Result<Expression> {
+	fn parse_map_expression(&mut self) -> Result<ExpressionKind> {
 		let mut pairs = Vec::new();
 
-		while self.peek_token != Token::RightBrace {
+		while self.peek_token.kind != TokenKind::RightBrace {
 			self.step()?;
 
 			let key = self.parse_expression(Precedence::Lowest)?;
 
-			assert_token!(peek, self, Token::Colon);
+			assert_token!(peek, self, TokenKind::Colon);
 			self.step()?;
 
 			let value = self.parse_expression(Precedence::Lowest)?;
 
 			pairs.push((key, value));
-			match &self.peek_token {
-				Token::RightBrace => {}
-				Token::Comma => self.step()?,
-				token => yeet!("Unexpected token `{token:?}`"),
+			match &self.peek_token.kind {
+				TokenKind::RightBrace => {}
+				TokenKind::Comma => self.step()?,
+				_ => yeet!(ParseError::UnexpectedToken {
+					got: self.peek_token.clone(),
+					expected: vec![TokenKind::Comma.describe(), TokenKind::RightBrace.describe()],
+				}),
 			};
 		}
 
-		assert_token!(peek, self, Token::RightBrace);
+		assert_token!(peek, self, TokenKind::RightBrace);
 
-		Ok(Expression::Map(pairs))
+		Ok(ExpressionKind::Map(pairs))
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn parse_if_expression(&mut self) -> Result<Expression> {
-		assert_token!(peek, self, Token::LeftParen);
+	fn parse_if_expression(&mut self) -> Result<ExpressionKind> {
+		assert_token!(peek, self, TokenKind::LeftParen);
 		self.step()?;
 
 		let condition = self.parse_expression(Precedence::Lowest)?;
 
-		assert_token!(peek, self, Token::RightParen);
-		assert_token!(peek, self, Token::LeftBrace);
+		assert_token!(peek, self, TokenKind::RightParen);
+		assert_token!(peek, self, TokenKind::LeftBrace);
 
 		let consequence = self.parse_block()?;
 		let mut alternative = None;
 
-		if self.peek_token == Token::Else {
+		if self.peek_token.kind == TokenKind::Else {
 			self.step()?;
-			assert_token!(peek, self, Token::LeftBrace);
+			assert_token!(peek, self, TokenKind::LeftBrace);
 			let block = self.parse_block()?;
 			alternative = Some(block);
 		}
 
-		Ok(Expression::Condition { condition: Box::new(condition), consequence, alternative })
+		Ok(ExpressionKind::Condition { condition: Box::new(condition), consequence, alternative })
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
-	fn parse_function(&mut self) -> Result<Expression> {
-		assert_token!(peek, self, Token::LeftParen);
+	fn parse_while_expression(&mut self) -> Result<ExpressionKind> {
+		assert_token!(peek, self, TokenKind::LeftParen);
+		self.step()?;
 
-		let parameters = self.parse_function_parameters()?;
+		let condition = self.parse_expression(Precedence::Lowest)?;
+
+		assert_token!(peek, self, TokenKind::RightParen);
+		assert_token!(peek, self, TokenKind::LeftBrace);
+
+		self.loop_depth += 1;
+		let body = self.parse_block();
+		self.loop_depth -= 1;
+
+		Ok(ExpressionKind::While { condition: Box::new(condition), body: body? })
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn parse_for_expression(&mut self) -> Result<ExpressionKind> {
+		assert_token!(peek, self, TokenKind::LeftParen);
+
+		let mut variables = vec![assert_token!(peek, self, TokenKind::Ident(ident) => {
+			ident.to_owned()
+		})];
+
+		if self.peek_token.kind == TokenKind::Comma {
+			self.step()?;
+			variables.push(assert_token!(peek, self, TokenKind::Ident(ident) => {
+				ident.to_owned()
+			}));
+		}
+
+		assert_token!(peek, self, TokenKind::In);
+		self.step()?;
+
+		let iterable = self.parse_expression(Precedence::Lowest)?;
+
+		assert_token!(peek, self, TokenKind::RightParen);
+		assert_token!(peek, self, TokenKind::LeftBrace);
 
-		assert_token!(peek, self, Token::LeftBrace);
+		self.loop_depth += 1;
+		let body = self.parse_block();
+		self.loop_depth -= 1;
 
+		Ok(ExpressionKind::For { variables, iterable: Box::new(iterable), body: body? })
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn parse_try_expression(&mut self) -> Result<ExpressionKind> {
+		assert_token!(peek, self, TokenKind::LeftBrace);
 		let body = self.parse_block()?;
 
-		Ok(Expression::Function { parameters, body })
+		assert_token!(peek, self, TokenKind::Catch);
+		assert_token!(peek, self, TokenKind::LeftParen);
+		let variable = assert_token!(peek, self, TokenKind::Ident(ident) => {
+			ident.to_owned()
+		});
+		assert_token!(peek, self, TokenKind::RightParen);
+		assert_token!(peek, self, TokenKind::LeftBrace);
+		let handler = self.parse_block()?;
+
+		let mut finally = None;
+		if self.peek_token.kind == TokenKind::Finally {
+			self.step()?;
+			assert_token!(peek, self, TokenKind::LeftBrace);
+			finally = Some(self.parse_block()?);
+		}
+
+		Ok(ExpressionKind::Try { body, variable, handler, finally })
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn parse_function(&mut self) -> Result<ExpressionKind> {
+		assert_token!(peek, self, TokenKind::LeftParen);
+
+		let parameters = self.parse_function_parameters()?;
+
+		assert_token!(peek, self, TokenKind::LeftBrace);
+
+		// Loops around the function don't count inside of its body.
+		let loop_depth = std::mem::take(&mut self.loop_depth);
+		self.function_depth += 1;
+		let body = self.parse_block();
+		self.function_depth -= 1;
+		self.loop_depth = loop_depth;
+		let body = body?;
+
+		Ok(ExpressionKind::Function { parameters, body })
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
@@ -249,23 +391,23 @@ impl Parser {
 		let mut identifiers = Vec::new();
 
 		self.step()?;
-		if self.current_token == Token::RightParen {
+		if self.current_token.kind == TokenKind::RightParen {
 			return Ok(identifiers);
 		}
 
-		assert_token!(current, self, Token::Ident(identifier) => {
+		assert_token!(current, self, TokenKind::Ident(identifier) => {
 			identifiers.push(identifier.to_owned());
 		});
 
-		while self.peek_token == Token::Comma {
+		while self.peek_token.kind == TokenKind::Comma {
 			self.step()?;
 			self.step()?;
-			assert_token!(current, self, Token::Ident(identifier) => {
+			assert_token!(current, self, TokenKind::Ident(identifier) => {
 				identifiers.push(identifier.to_owned());
 			});
 		}
 
-		assert_token!(peek, self, Token::RightParen);
+		assert_token!(peek, self, TokenKind::RightParen);
 
 		Ok(identifiers)
 	}
@@ -275,37 +417,93 @@ impl Parser {
 		let mut statements = Vec::new();
 		self.step()?;
 
-		while !matches!(self.current_token, Token::RightBrace | Token::Eof) {
+		while !matches!(self.current_token.kind, TokenKind::RightBrace | TokenKind::Eof) {
 			let statement = self.parse_statement()?;
 			statements.push(statement);
 			self.step()?;
 		}
 
+		assert_token!(current, self, TokenKind::RightBrace);
+
 		Ok(statements)
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
 	fn parse_call(&mut self, function: Expression) -> Result<Expression> {
-		if !matches!(function, Expression::Identifier(_) | Expression::Function { .. }) {
-			yeet!("Invalid function expression `{function:?}`");
+		if !matches!(function.kind, ExpressionKind::Identifier(_) | ExpressionKind::Function { .. })
+		{
+			yeet!(ParseError::InvalidCallee { function: Box::new(function) });
 		}
 
 		let arguments = self.parse_call_arguments()?;
-		Ok(Expression::Call { function: Box::new(function), arguments })
+		let span = function.span.to(self.current_token.span);
+		Ok(Expression::new(ExpressionKind::Call { function: Box::new(function), arguments }, span))
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn parse_assignment(&mut self, target: Expression) -> Result<Expression> {
+		if !target.is_assignable() {
+			yeet!(ParseError::InvalidAssignmentTarget { target: Box::new(target) });
+		}
+
+		let operator = match self.current_token.kind {
+			TokenKind::PlusAssign => Some(InfixOperator::Add),
+			TokenKind::MinusAssign => Some(InfixOperator::Sub),
+			TokenKind::AsteriskAssign => Some(InfixOperator::Mul),
+			TokenKind::SlashAssign => Some(InfixOperator::Div),
+			TokenKind::PercentAssign => Some(InfixOperator::Modulo),
+			_ => None,
+		};
+
+		// Assignments are right associative, so `a = b = c` assigns `c` to both.
+		self.step()?;
+		let value = self.parse_expression(Precedence::Lowest)?;
+		let span = target.span.to(value.span);
+		let kind =
+			ExpressionKind::Assign { target: Box::new(target), operator, value: Box::new(value) };
+
+		Ok(Expression::new(kind, span))
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn parse_range(&mut self, start: Expression) -> Result<Expression> {
+		let inclusive = self.current_token.kind == TokenKind::DotDotEq;
+		let precedence = self.current_token.precedence();
+		self.step()?;
+
+		let end = self.parse_expression(precedence)?;
+		let span = start.span.to(end.span);
+		let kind = ExpressionKind::Range { start: Box::new(start), end: Box::new(end), inclusive };
+
+		Ok(Expression::new(kind, span))
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
 	fn parse_index_expression(&mut self, lhs: Expression) -> Result<Expression> {
 		self.step()?;
 
-		let expression = Expression::Index {
+		let start = lhs.span;
+		let kind = ExpressionKind::Index {
 			lhs: Box::new(lhs),
 			idx: Box::new(self.parse_expression(Precedence::Lowest)?),
 		};
 
-		assert_token!(peek, self, Token::RightBracket);
+		assert_token!(peek, self, TokenKind::RightBracket);
 
-		Ok(expression)
+		Ok(Expression::new(kind, start.to(self.current_token.span)))
+	}
+
+	#[tracing::instrument(level = "DEBUG", ret)]
+	fn parse_propagate(&mut self, value: Expression) -> Expression {
+		// Like `return`, this leaves the current function, so there has to be one.
+		if self.function_depth == 0 {
+			let token = self.current_token.clone();
+			self.errors
+				.push(ParseError::ReturnOutsideFunction { token });
+		}
+
+		let span = value.span.to(self.current_token.span);
+		Expression::new(ExpressionKind::Propagate { value: Box::new(value) }, span)
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
@@ -313,46 +511,59 @@ impl Parser {
 		let mut arguments = Vec::new();
 
 		self.step()?;
-		if self.current_token == Token::RightParen {
+		if self.current_token.kind == TokenKind::RightParen {
 			return Ok(arguments);
 		}
 
 		arguments.push(self.parse_expression(Precedence::Lowest)?);
-		while self.peek_token == Token::Comma {
+		while self.peek_token.kind == TokenKind::Comma {
 			self.step()?;
 			self.step()?;
 			arguments.push(self.parse_expression(Precedence::Lowest)?);
 		}
 
-		assert_token!(peek, self, Token::RightParen);
+		assert_token!(peek, self, TokenKind::RightParen);
 
 		Ok(arguments)
 	}
 
 	#[tracing::instrument(level = "DEBUG", ret)]
 	pub fn parse_infix(&mut self, lhs: Expression) -> Result<StdResult<Expression, Expression>> {
-		let operator = match &self.current_token {
-			Token::Equal => InfixOperator::Equal,
-			Token::NotEqual => InfixOperator::NotEqual,
-			Token::Plus => InfixOperator::Add,
-			Token::Minus => InfixOperator::Sub,
-			Token::Asterisk => InfixOperator::Mul,
-			Token::Slash => InfixOperator::Div,
-			Token::LessThan if self.peek_token == Token::Assign => InfixOperator::LessThanOrEqual,
-			Token::LessThan => InfixOperator::LessThan,
-			Token::GreaterThan if self.peek_token == Token::Assign => {
+		let operator = match &self.current_token.kind {
+			TokenKind::Equal => InfixOperator::Equal,
+			TokenKind::NotEqual => InfixOperator::NotEqual,
+			TokenKind::Plus => InfixOperator::Add,
+			TokenKind::Minus => InfixOperator::Sub,
+			TokenKind::Asterisk => InfixOperator::Mul,
+			TokenKind::Slash => InfixOperator::Div,
+			TokenKind::Percent => InfixOperator::Modulo,
+			TokenKind::LessThan if self.peek_token.kind == TokenKind::Assign => {
+				InfixOperator::LessThanOrEqual
+			}
+			TokenKind::LessThan => InfixOperator::LessThan,
+			TokenKind::GreaterThan if self.peek_token.kind == TokenKind::Assign => {
 				InfixOperator::GreaterThanOrEqual
 			}
-			Token::GreaterThan => InfixOperator::GreaterThan,
-			Token::LeftParen => return Ok(Ok(self.parse_call(lhs)?)),
-			Token::LeftBracket => return Ok(Ok(self.parse_index_expression(lhs)?)),
+			TokenKind::GreaterThan => InfixOperator::GreaterThan,
+			TokenKind::Assign
+			| TokenKind::PlusAssign
+			| TokenKind::MinusAssign
+			| TokenKind::AsteriskAssign
+			| TokenKind::SlashAssign
+			| TokenKind::PercentAssign => return Ok(Ok(self.parse_assignment(lhs)?)),
+			TokenKind::DotDot | TokenKind::DotDotEq => return Ok(Ok(self.parse_range(lhs)?)),
+			TokenKind::LeftParen => return Ok(Ok(self.parse_call(lhs)?)),
+			TokenKind::LeftBracket => return Ok(Ok(self.parse_index_expression(lhs)?)),
+			TokenKind::Question => return Ok(Ok(self.parse_propagate(lhs))),
 			_ => return Ok(Err(lhs)),
 		};
 
 		let precedence = self.current_token.precedence();
 		self.step()?;
 		let rhs = self.parse_expression(precedence)?;
-		Ok(Ok(Expression::Infix { operator, lhs: Box::new(lhs), rhs: Box::new(rhs) }))
+		let span = lhs.span.to(rhs.span);
+		let kind = ExpressionKind::Infix { operator, lhs: Box::new(lhs), rhs: Box::new(rhs) };
+		Ok(Ok(Expression::new(kind, span)))
 	}
 }
 
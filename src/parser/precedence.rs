@@ -1,10 +1,12 @@
-use crate::Token;
+use crate::TokenKind;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Precedence {
 	Lowest,
+	Assign,
 	Equals,
 	LessGreater,
+	Range,
 	Sum,
 	Product,
 	Prefix,
@@ -12,16 +14,23 @@ pub enum Precedence {
 	Index,
 }
 
-impl From<&Token> for Precedence {
+impl From<&TokenKind> for Precedence {
 	#[tracing::instrument(level = "TRACE", ret)]
-	fn from(token: &Token) -> Self {
+	fn from(token: &TokenKind) -> Self {
 		match token {
-			Token::Equal | Token::NotEqual => Self::Equals,
-			Token::Plus | Token::Minus => Self::Sum,
-			Token::Asterisk | Token::Slash => Self::Product,
-			Token::LessThan | Token::GreaterThan => Self::LessGreater,
-			Token::LeftParen => Self::Call,
-			Token::LeftBracket => Self::Index,
+			TokenKind::Assign
+			| TokenKind::PlusAssign
+			| TokenKind::MinusAssign
+			| TokenKind::AsteriskAssign
+			| TokenKind::SlashAssign
+			| TokenKind::PercentAssign => Self::Assign,
+			TokenKind::Equal | TokenKind::NotEqual => Self::Equals,
+			TokenKind::Plus | TokenKind::Minus => Self::Sum,
+			TokenKind::Asterisk | TokenKind::Slash | TokenKind::Percent => Self::Product,
+			TokenKind::LessThan | TokenKind::GreaterThan => Self::LessGreater,
+			TokenKind::DotDot | TokenKind::DotDotEq => Self::Range,
+			TokenKind::LeftParen | TokenKind::Question => Self::Call,
+			TokenKind::LeftBracket => Self::Index,
 			_ => Self::Lowest,
 		}
 	}
@@ -1,7 +1,8 @@
 use {
 	crate::{
 		ast::{InfixOperator, PrefixOperator},
-		Expression, Parser, Statement, Tokenizer,
+		Expression, ExpressionKind, ParseError, Parser, Statement, StatementKind, TokenKind,
+		Tokenizer,
 	},
 	color_eyre::{eyre::bail as yeet, Result},
 	pretty_assertions::assert_eq,
@@ -34,23 +35,35 @@ fn let_statements() -> Result<()> {
 #[test]
 fn return_statements() -> Result<()> {
 	let input = r#"
-		return 5;
-		return 10;
-		return 993322;
+		fn() {
+			return 5;
+			return 10;
+			return 993322;
+		}
 	"#
 	.chars()
 	.collect();
 
 	let tokenizer = Tokenizer::new(input);
 	let mut parser = Parser::new(tokenizer)?;
-	let program = parser.parse_program();
+	let mut program = parser.parse_program();
 	let errors = parser.errors.len();
 
 	assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
-	assert_eq!(program.statements.len(), 3);
-	assert_eq!(program.statements[0], Statement::r#return(5));
-	assert_eq!(program.statements[1], Statement::r#return(10));
-	assert_eq!(program.statements[2], Statement::r#return(993322));
+	assert_eq!(program.statements.len(), 1);
+
+	let StatementKind::Expression(expression) = program.statements.remove(0).kind else {
+		yeet!("Expected expression statement");
+	};
+
+	let ExpressionKind::Function { body, .. } = expression.kind else {
+		yeet!("Expected function expression but got {expression:?}");
+	};
+
+	assert_eq!(body.len(), 3);
+	assert_eq!(body[0], Statement::r#return(5));
+	assert_eq!(body[1], Statement::r#return(10));
+	assert_eq!(body[2], Statement::r#return(993322));
 
 	Ok(())
 }
@@ -87,7 +100,10 @@ fn integer_literal_expression() -> Result<()> {
 
 #[test]
 fn boolean_literal_expression() -> Result<()> {
-	let test_cases = [("true;", Expression::Bool(true)), ("false;", Expression::Bool(false))];
+	let test_cases = [
+		("true;", Expression::from(ExpressionKind::Bool(true))),
+		("false;", Expression::from(ExpressionKind::Bool(false))),
+	];
 
 	for (input, expected) in test_cases {
 		let tokenizer = Tokenizer::new(input.chars().collect());
@@ -97,7 +113,7 @@ fn boolean_literal_expression() -> Result<()> {
 
 		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
 		assert_eq!(program.statements.len(), 1);
-		assert_eq!(program.statements[0], Statement::Expression(expected));
+		assert_eq!(program.statements[0], Statement::expression(expected));
 	}
 
 	Ok(())
@@ -115,7 +131,7 @@ fn string_expression() -> Result<()> {
 	assert_eq!(program.statements.len(), 1);
 	assert_eq!(
 		program.statements[0],
-		Statement::Expression(Expression::String(String::from("foobar")))
+		Statement::expression(Expression::from(ExpressionKind::String(String::from("foobar"))))
 	);
 
 	Ok(())
@@ -133,19 +149,19 @@ fn array_expression() -> Result<()> {
 	assert_eq!(program.statements.len(), 1);
 	assert_eq!(
 		program.statements[0],
-		Statement::Expression(Expression::Array(vec![
-			Expression::Int(1),
-			Expression::Infix {
+		Statement::expression(Expression::from(ExpressionKind::Array(vec![
+			Expression::from(ExpressionKind::Int(1)),
+			Expression::from(ExpressionKind::Infix {
 				operator: InfixOperator::Mul,
 				lhs: Box::new(2.into()),
 				rhs: Box::new(2.into()),
-			},
-			Expression::Infix {
+			}),
+			Expression::from(ExpressionKind::Infix {
 				operator: InfixOperator::Add,
 				lhs: Box::new(3.into()),
 				rhs: Box::new(3.into()),
-			}
-		]))
+			})
+		])))
 	);
 
 	Ok(())
@@ -154,7 +170,7 @@ fn array_expression() -> Result<()> {
 #[test]
 fn map_expression() -> Result<()> {
 	let test_cases = [
-		("{}", Expression::Map(Vec::new())),
+		("{}", Expression::from(ExpressionKind::Map(Vec::new()))),
 		(
 			r#"
 				{
@@ -163,11 +179,11 @@ fn map_expression() -> Result<()> {
 					"three": 3
 				}
 			"#,
-			Expression::Map(vec![
-				(Expression::String("one".into()), 1.into()),
-				(Expression::String("two".into()), 2.into()),
-				(Expression::String("three".into()), 3.into()),
-			]),
+			Expression::from(ExpressionKind::Map(vec![
+				(Expression::from(ExpressionKind::String("one".into())), 1.into()),
+				(Expression::from(ExpressionKind::String("two".into())), 2.into()),
+				(Expression::from(ExpressionKind::String("three".into())), 3.into()),
+			])),
 		),
 		(
 			r#"
@@ -177,23 +193,32 @@ fn map_expression() -> Result<()> {
 					"three": 15 / 5
 				}
 			"#,
-			Expression::Map(vec![
-				(Expression::String("one".into()), Expression::Infix {
-					operator: InfixOperator::Add,
-					lhs: Box::new(0.into()),
-					rhs: Box::new(1.into()),
-				}),
-				(Expression::String("two".into()), Expression::Infix {
-					operator: InfixOperator::Sub,
-					lhs: Box::new(10.into()),
-					rhs: Box::new(8.into()),
-				}),
-				(Expression::String("three".into()), Expression::Infix {
-					operator: InfixOperator::Div,
-					lhs: Box::new(15.into()),
-					rhs: Box::new(5.into()),
-				}),
-			]),
+			Expression::from(ExpressionKind::Map(vec![
+				(
+					Expression::from(ExpressionKind::String("one".into())),
+					Expression::from(ExpressionKind::Infix {
+						operator: InfixOperator::Add,
+						lhs: Box::new(0.into()),
+						rhs: Box::new(1.into()),
+					}),
+				),
+				(
+					Expression::from(ExpressionKind::String("two".into())),
+					Expression::from(ExpressionKind::Infix {
+						operator: InfixOperator::Sub,
+						lhs: Box::new(10.into()),
+						rhs: Box::new(8.into()),
+					}),
+				),
+				(
+					Expression::from(ExpressionKind::String("three".into())),
+					Expression::from(ExpressionKind::Infix {
+						operator: InfixOperator::Div,
+						lhs: Box::new(15.into()),
+						rhs: Box::new(5.into()),
+					}),
+				),
+			])),
 		),
 	];
 
@@ -205,7 +230,7 @@ fn map_expression() -> Result<()> {
 
 		eprintln!("Parser had {errors} error(s): {:#?}", parser.errors);
 		assert_eq!(program.statements.len(), 1);
-		assert_eq!(program.statements.remove(0), Statement::Expression(expected));
+		assert_eq!(program.statements.remove(0), Statement::expression(expected));
 	}
 
 	Ok(())
@@ -214,22 +239,34 @@ fn map_expression() -> Result<()> {
 #[test]
 fn parsing_prefix_expressions() -> Result<()> {
 	let test_cases = [
-		("!5;", Expression::Prefix {
-			operator: PrefixOperator::Not,
-			rhs: Box::new(Expression::Int(5)),
-		}),
-		("-15;", Expression::Prefix {
-			operator: PrefixOperator::Neg,
-			rhs: Box::new(Expression::Int(15)),
-		}),
-		("!true;", Expression::Prefix {
-			operator: PrefixOperator::Not,
-			rhs: Box::new(Expression::Bool(true)),
-		}),
-		("!false;", Expression::Prefix {
-			operator: PrefixOperator::Not,
-			rhs: Box::new(Expression::Bool(false)),
-		}),
+		(
+			"!5;",
+			Expression::from(ExpressionKind::Prefix {
+				operator: PrefixOperator::Not,
+				rhs: Box::new(Expression::from(ExpressionKind::Int(5))),
+			}),
+		),
+		(
+			"-15;",
+			Expression::from(ExpressionKind::Prefix {
+				operator: PrefixOperator::Neg,
+				rhs: Box::new(Expression::from(ExpressionKind::Int(15))),
+			}),
+		),
+		(
+			"!true;",
+			Expression::from(ExpressionKind::Prefix {
+				operator: PrefixOperator::Not,
+				rhs: Box::new(Expression::from(ExpressionKind::Bool(true))),
+			}),
+		),
+		(
+			"!false;",
+			Expression::from(ExpressionKind::Prefix {
+				operator: PrefixOperator::Not,
+				rhs: Box::new(Expression::from(ExpressionKind::Bool(false))),
+			}),
+		),
 	];
 
 	for (input, expected) in test_cases {
@@ -241,7 +278,7 @@ fn parsing_prefix_expressions() -> Result<()> {
 		eprintln!("Parser had {errors} error(s): {:#?}", parser.errors);
 		assert_eq!(program.statements.len(), 1);
 		let statement = program.statements.remove(0);
-		let Statement::Expression(expression) = statement else {
+		let StatementKind::Expression(expression) = statement.kind else {
 			yeet!("Statement was not an expression ({statement:?})");
 		};
 
@@ -254,61 +291,94 @@ fn parsing_prefix_expressions() -> Result<()> {
 #[test]
 fn parsing_infix_expressions() -> Result<()> {
 	let test_cases = [
-		("5 + 5;", Expression::Infix {
-			operator: InfixOperator::Add,
-			lhs: Box::new(5.into()),
-			rhs: Box::new(5.into()),
-		}),
-		("5 - 5;", Expression::Infix {
-			operator: InfixOperator::Sub,
-			lhs: Box::new(5.into()),
-			rhs: Box::new(5.into()),
-		}),
-		("5 * 5;", Expression::Infix {
-			operator: InfixOperator::Mul,
-			lhs: Box::new(5.into()),
-			rhs: Box::new(5.into()),
-		}),
-		("5 / 5;", Expression::Infix {
-			operator: InfixOperator::Div,
-			lhs: Box::new(5.into()),
-			rhs: Box::new(5.into()),
-		}),
-		("5 > 5;", Expression::Infix {
-			operator: InfixOperator::GreaterThan,
-			lhs: Box::new(5.into()),
-			rhs: Box::new(5.into()),
-		}),
-		("5 < 5;", Expression::Infix {
-			operator: InfixOperator::LessThan,
-			lhs: Box::new(5.into()),
-			rhs: Box::new(5.into()),
-		}),
-		("5 == 5;", Expression::Infix {
-			operator: InfixOperator::Equal,
-			lhs: Box::new(5.into()),
-			rhs: Box::new(5.into()),
-		}),
-		("5 != 5;", Expression::Infix {
-			operator: InfixOperator::NotEqual,
-			lhs: Box::new(5.into()),
-			rhs: Box::new(5.into()),
-		}),
-		("true == true", Expression::Infix {
-			operator: InfixOperator::Equal,
-			lhs: Box::new(true.into()),
-			rhs: Box::new(true.into()),
-		}),
-		("true != false", Expression::Infix {
-			operator: InfixOperator::NotEqual,
-			lhs: Box::new(true.into()),
-			rhs: Box::new(false.into()),
-		}),
-		("false == false", Expression::Infix {
-			operator: InfixOperator::Equal,
-			lhs: Box::new(false.into()),
-			rhs: Box::new(false.into()),
-		}),
+		(
+			"5 + 5;",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::Add,
+				lhs: Box::new(5.into()),
+				rhs: Box::new(5.into()),
+			}),
+		),
+		(
+			"5 - 5;",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::Sub,
+				lhs: Box::new(5.into()),
+				rhs: Box::new(5.into()),
+			}),
+		),
+		(
+			"5 * 5;",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::Mul,
+				lhs: Box::new(5.into()),
+				rhs: Box::new(5.into()),
+			}),
+		),
+		(
+			"5 / 5;",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::Div,
+				lhs: Box::new(5.into()),
+				rhs: Box::new(5.into()),
+			}),
+		),
+		(
+			"5 > 5;",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::GreaterThan,
+				lhs: Box::new(5.into()),
+				rhs: Box::new(5.into()),
+			}),
+		),
+		(
+			"5 < 5;",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::LessThan,
+				lhs: Box::new(5.into()),
+				rhs: Box::new(5.into()),
+			}),
+		),
+		(
+			"5 == 5;",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::Equal,
+				lhs: Box::new(5.into()),
+				rhs: Box::new(5.into()),
+			}),
+		),
+		(
+			"5 != 5;",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::NotEqual,
+				lhs: Box::new(5.into()),
+				rhs: Box::new(5.into()),
+			}),
+		),
+		(
+			"true == true",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::Equal,
+				lhs: Box::new(true.into()),
+				rhs: Box::new(true.into()),
+			}),
+		),
+		(
+			"true != false",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::NotEqual,
+				lhs: Box::new(true.into()),
+				rhs: Box::new(false.into()),
+			}),
+		),
+		(
+			"false == false",
+			Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::Equal,
+				lhs: Box::new(false.into()),
+				rhs: Box::new(false.into()),
+			}),
+		),
 	];
 
 	for (input, expected) in test_cases {
@@ -320,7 +390,7 @@ fn parsing_infix_expressions() -> Result<()> {
 		eprintln!("Parser had {errors} error(s): {:#?}", parser.errors);
 		assert_eq!(program.statements.len(), 1);
 		let statement = program.statements.remove(0);
-		let Statement::Expression(expression) = statement else {
+		let StatementKind::Expression(expression) = statement.kind else {
 			yeet!("Statement was not an expression ({statement:?})");
 		};
 
@@ -339,6 +409,7 @@ fn operator_precedence() -> Result<()> {
 		("a + b - c", "((a + b) - c)"),
 		("a * b * c", "((a * b) * c)"),
 		("a * b / c", "((a * b) / c)"),
+		("a + b % c * d", "(a + ((b % c) * d))"),
 		("a + b / c", "(a + (b / c))"),
 		("a + b * c + d / e - f", "(((a + (b * c)) + (d / e)) - f)"),
 		("3 + 4; -5 * 5", "(3 + 4)((-5) * 5)"),
@@ -362,6 +433,18 @@ fn operator_precedence() -> Result<()> {
 		("add(a + b + c * d / f + g)", "add((((a + b) + ((c * d) / f)) + g))"),
 		("a * [1, 2, 3, 4][b * c] * d", "((a * ([1, 2, 3, 4][(b * c)])) * d)"),
 		("add(a * b[2], b[1], 2 * [1, 2][1])", "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))"),
+		("a = b + 1", "(a = (b + 1))"),
+		("a = b = c", "(a = (b = c))"),
+		("a += b * 2", "(a += (b * 2))"),
+		("a[0][b] -= 1", "(((a[0])[b]) -= 1)"),
+		("x *= y /= 2", "(x *= (y /= 2))"),
+		("x %= y + 2", "(x %= (y + 2))"),
+		("0..n + 1", "(0..(n + 1))"),
+		("a..=b * 2 == c", "((a..=(b * 2)) == c)"),
+		("x = 1..2", "(x = (1..2))"),
+		("a + f(b)? * c", "(a + ((f(b)?) * c))"),
+		("-a?", "(-(a?))"),
+		("a[0]?[1]", "(((a[0])?)[1])"),
 	];
 
 	for (input, expected) in test_cases {
@@ -388,19 +471,22 @@ fn parsing_if_expressions() -> Result<()> {
 	eprintln!("Parser had {errors} error(s): {:#?}", parser.errors);
 	assert_eq!(program.statements.len(), 1);
 	let statement = program.statements.remove(0);
-	let Statement::Expression(expression) = statement else {
+	let StatementKind::Expression(expression) = statement.kind else {
 		yeet!("Statement was not an expression ({statement:?})");
 	};
 
-	assert_eq!(expression, Expression::Condition {
-		condition: Box::new(Expression::Infix {
-			operator: InfixOperator::LessThan,
-			lhs: Box::new("x".into()),
-			rhs: Box::new("y".into())
-		}),
-		consequence: vec![Statement::expression("x")],
-		alternative: None,
-	});
+	assert_eq!(
+		expression,
+		Expression::from(ExpressionKind::Condition {
+			condition: Box::new(Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::LessThan,
+				lhs: Box::new("x".into()),
+				rhs: Box::new("y".into())
+			})),
+			consequence: vec![Statement::expression("x")],
+			alternative: None,
+		})
+	);
 
 	Ok(())
 }
@@ -416,19 +502,200 @@ fn parsing_if_else_expressions() -> Result<()> {
 	eprintln!("Parser had {errors} error(s): {:#?}", parser.errors);
 	assert_eq!(program.statements.len(), 1);
 	let statement = program.statements.remove(0);
-	let Statement::Expression(expression) = statement else {
+	let StatementKind::Expression(expression) = statement.kind else {
 		yeet!("Statement was not an expression ({statement:?})");
 	};
 
-	assert_eq!(expression, Expression::Condition {
-		condition: Box::new(Expression::Infix {
-			operator: InfixOperator::LessThan,
-			lhs: Box::new("x".into()),
-			rhs: Box::new("y".into())
-		}),
-		consequence: vec![Statement::expression("x")],
-		alternative: Some(vec![Statement::expression("y")]),
-	});
+	assert_eq!(
+		expression,
+		Expression::from(ExpressionKind::Condition {
+			condition: Box::new(Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::LessThan,
+				lhs: Box::new("x".into()),
+				rhs: Box::new("y".into())
+			})),
+			consequence: vec![Statement::expression("x")],
+			alternative: Some(vec![Statement::expression("y")]),
+		})
+	);
+
+	Ok(())
+}
+
+#[test]
+fn parsing_while_expressions() -> Result<()> {
+	let input = "while (x < y) { if (x) { break; } continue }"
+		.chars()
+		.collect();
+	let tokenizer = Tokenizer::new(input);
+	let mut parser = Parser::new(tokenizer)?;
+	let mut program = parser.parse_program();
+	let errors = parser.errors.len();
+
+	assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+	assert_eq!(program.statements.len(), 1);
+	let statement = program.statements.remove(0);
+	let StatementKind::Expression(expression) = statement.kind else {
+		yeet!("Statement was not an expression ({statement:?})");
+	};
+
+	assert_eq!(
+		expression,
+		Expression::from(ExpressionKind::While {
+			condition: Box::new(Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::LessThan,
+				lhs: Box::new("x".into()),
+				rhs: Box::new("y".into())
+			})),
+			body: vec![
+				Statement::expression(ExpressionKind::Condition {
+					condition: Box::new("x".into()),
+					consequence: vec![StatementKind::Break.into()],
+					alternative: None,
+				}),
+				StatementKind::Continue.into(),
+			],
+		})
+	);
+
+	Ok(())
+}
+
+#[test]
+fn parsing_try_expressions() -> Result<()> {
+	let test_cases = [
+		("try { throw x; } catch (e) { e }", None),
+		("try { throw x; } catch (e) { e } finally { y }", Some(vec![Statement::expression("y")])),
+	];
+
+	for (input, finally) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let mut program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+		assert_eq!(program.statements.len(), 1);
+		let statement = program.statements.remove(0);
+		let StatementKind::Expression(expression) = statement.kind else {
+			yeet!("Statement was not an expression ({statement:?})");
+		};
+
+		assert_eq!(
+			expression,
+			Expression::from(ExpressionKind::Try {
+				body: vec![StatementKind::Throw { value: "x".into() }.into()],
+				variable: String::from("e"),
+				handler: vec![Statement::expression("e")],
+				finally,
+			})
+		);
+	}
+
+	let tokenizer = Tokenizer::new("try { x } finally { y }".chars().collect());
+	let mut parser = Parser::new(tokenizer)?;
+	parser.parse_program();
+	assert!(!parser.errors.is_empty(), "`catch` should be required");
+
+	Ok(())
+}
+
+#[test]
+fn parsing_for_expressions() -> Result<()> {
+	let test_cases = [
+		("for (x in xs) { x }", vec!["x"], "xs"),
+		("for (k, v in map) { x }", vec!["k", "v"], "map"),
+	];
+
+	for (input, variables, iterable) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		let mut program = parser.parse_program();
+		let errors = parser.errors.len();
+
+		assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+		assert_eq!(program.statements.len(), 1);
+		let statement = program.statements.remove(0);
+		let StatementKind::Expression(expression) = statement.kind else {
+			yeet!("Statement was not an expression ({statement:?})");
+		};
+
+		assert_eq!(
+			expression,
+			Expression::from(ExpressionKind::For {
+				variables: variables.into_iter().map(String::from).collect(),
+				iterable: Box::new(iterable.into()),
+				body: vec![Statement::expression("x")],
+			})
+		);
+	}
+
+	for input in ["for (x) { x }", "for (a, b, c in xs) { x }", "for x in xs { x }"] {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		parser.parse_program();
+
+		assert!(!parser.errors.is_empty(), "`{input}` should not parse");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn loop_control_outside_of_loops() -> Result<()> {
+	let test_cases = [
+		("break;", 1),
+		("continue", 1),
+		("if (x) { break; }", 1),
+		("while (x) { fn() { break; } }", 1),
+		("fn() { while (x) { continue; } }", 0),
+		("while (x) { while (y) { break; } break; }", 0),
+		("for (x in xs) { continue; }", 0),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		parser.parse_program();
+
+		assert_eq!(parser.errors.len(), expected, "{input}: {:#?}", parser.errors);
+		assert!(
+			parser
+				.errors
+				.iter()
+				.all(|error| matches!(error, ParseError::LoopControlOutsideLoop { .. }))
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn return_outside_of_functions() -> Result<()> {
+	let test_cases = [
+		("return 1;", 1),
+		("if (x) { return 1; }", 1),
+		("while (x) { return 1; }", 1),
+		("fn() { return 1; }; return 2;", 1),
+		("fn() { if (x) { return 1; } }", 0),
+		("fn() { fn() { return 1; }; return 2; }", 0),
+		("f()?", 1),
+		("fn() { f()? }; g()?", 1),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		parser.parse_program();
+
+		assert_eq!(parser.errors.len(), expected, "{input}: {:#?}", parser.errors);
+		assert!(
+			parser
+				.errors
+				.iter()
+				.all(|error| matches!(error, ParseError::ReturnOutsideFunction { .. }))
+		);
+	}
 
 	Ok(())
 }
@@ -444,18 +711,21 @@ fn parsing_function_expressions() -> Result<()> {
 	eprintln!("Parser had {errors} error(s): {:#?}", parser.errors);
 	assert_eq!(program.statements.len(), 1);
 	let statement = program.statements.remove(0);
-	let Statement::Expression(expression) = statement else {
+	let StatementKind::Expression(expression) = statement.kind else {
 		yeet!("Statement was not an expression ({statement:?})");
 	};
 
-	assert_eq!(expression, Expression::Function {
-		parameters: vec![String::from("x"), String::from("y")],
-		body: vec![Statement::Expression(Expression::Infix {
-			operator: InfixOperator::Add,
-			lhs: Box::new("x".into()),
-			rhs: Box::new("y".into())
-		})],
-	});
+	assert_eq!(
+		expression,
+		Expression::from(ExpressionKind::Function {
+			parameters: vec![String::from("x"), String::from("y")],
+			body: vec![Statement::expression(Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::Add,
+				lhs: Box::new("x".into()),
+				rhs: Box::new("y".into())
+			}))],
+		})
+	);
 
 	Ok(())
 }
@@ -477,11 +747,14 @@ fn parsing_function_parameters() -> Result<()> {
 		eprintln!("Parser had {errors} error(s): {:#?}", parser.errors);
 		assert_eq!(program.statements.len(), 1);
 		let statement = program.statements.remove(0);
-		let Statement::Expression(expression) = statement else {
+		let StatementKind::Expression(expression) = statement.kind else {
 			yeet!("Statement was not an expression ({statement:?})");
 		};
 
-		assert_eq!(expression, Expression::Function { parameters: expected, body: vec![] });
+		assert_eq!(
+			expression,
+			Expression::from(ExpressionKind::Function { parameters: expected, body: vec![] })
+		);
 	}
 
 	Ok(())
@@ -498,26 +771,29 @@ fn parsing_call_expressions() -> Result<()> {
 	eprintln!("Parser had {errors} error(s): {:#?}", parser.errors);
 	assert_eq!(program.statements.len(), 1);
 	let statement = program.statements.remove(0);
-	let Statement::Expression(expression) = statement else {
+	let StatementKind::Expression(expression) = statement.kind else {
 		yeet!("Statement was not an expression ({statement:?})");
 	};
 
-	assert_eq!(expression, Expression::Call {
-		function: Box::new("add".into()),
-		arguments: vec![
-			1.into(),
-			Expression::Infix {
-				operator: InfixOperator::Mul,
-				lhs: Box::new(2.into()),
-				rhs: Box::new(3.into()),
-			},
-			Expression::Infix {
-				operator: InfixOperator::Add,
-				lhs: Box::new(4.into()),
-				rhs: Box::new(5.into()),
-			},
-		],
-	});
+	assert_eq!(
+		expression,
+		Expression::from(ExpressionKind::Call {
+			function: Box::new("add".into()),
+			arguments: vec![
+				1.into(),
+				Expression::from(ExpressionKind::Infix {
+					operator: InfixOperator::Mul,
+					lhs: Box::new(2.into()),
+					rhs: Box::new(3.into()),
+				}),
+				Expression::from(ExpressionKind::Infix {
+					operator: InfixOperator::Add,
+					lhs: Box::new(4.into()),
+					rhs: Box::new(5.into()),
+				}),
+			],
+		})
+	);
 
 	Ok(())
 }
@@ -533,18 +809,21 @@ fn parsing_index_expressions() -> Result<()> {
 	eprintln!("Parser had {errors} error(s): {:#?}", parser.errors);
 	assert_eq!(program.statements.len(), 1);
 	let statement = program.statements.remove(0);
-	let Statement::Expression(expression) = statement else {
+	let StatementKind::Expression(expression) = statement.kind else {
 		yeet!("Statement was not an expression ({statement:?})");
 	};
 
-	assert_eq!(expression, Expression::Index {
-		lhs: Box::new("my_array".into()),
-		idx: Box::new(Expression::Infix {
-			operator: InfixOperator::Add,
-			lhs: Box::new(1.into()),
-			rhs: Box::new(1.into()),
+	assert_eq!(
+		expression,
+		Expression::from(ExpressionKind::Index {
+			lhs: Box::new("my_array".into()),
+			idx: Box::new(Expression::from(ExpressionKind::Infix {
+				operator: InfixOperator::Add,
+				lhs: Box::new(1.into()),
+				rhs: Box::new(1.into()),
+			}))
 		})
-	});
+	);
 
 	Ok(())
 }
@@ -553,19 +832,19 @@ fn parsing_index_expressions() -> Result<()> {
 fn parsing_call_parameters() -> Result<()> {
 	let test_cases = [
 		("add();", "add", vec![]),
-		("add(1);", "add", vec![Expression::Int(1)]),
+		("add(1);", "add", vec![Expression::from(ExpressionKind::Int(1))]),
 		("add(1, 2 * 3, 4 + 5);", "add", vec![
-			Expression::Int(1),
-			Expression::Infix {
+			Expression::from(ExpressionKind::Int(1)),
+			Expression::from(ExpressionKind::Infix {
 				operator: InfixOperator::Mul,
 				lhs: Box::new(2.into()),
 				rhs: Box::new(3.into()),
-			},
-			Expression::Infix {
+			}),
+			Expression::from(ExpressionKind::Infix {
 				operator: InfixOperator::Add,
 				lhs: Box::new(4.into()),
 				rhs: Box::new(5.into()),
-			},
+			}),
 		]),
 	];
 
@@ -578,14 +857,139 @@ fn parsing_call_parameters() -> Result<()> {
 		eprintln!("Parser had {errors} error(s): {:#?}", parser.errors);
 		assert_eq!(program.statements.len(), 1);
 		let statement = program.statements.remove(0);
-		let Statement::Expression(expression) = statement else {
+		let StatementKind::Expression(expression) = statement.kind else {
 			yeet!("Statement was not an expression ({statement:?})");
 		};
 
-		assert_eq!(expression, Expression::Call {
-			function: Box::new(identifier.into()),
-			arguments
-		});
+		assert_eq!(
+			expression,
+			Expression::from(ExpressionKind::Call {
+				function: Box::new(identifier.into()),
+				arguments
+			})
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn invalid_assignment_targets() -> Result<()> {
+	for input in ["1 = 2", "f() = 2", "a + b = c", "f()[0] += 1"] {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let mut parser = Parser::new(tokenizer)?;
+		parser.parse_program();
+
+		assert!(
+			matches!(parser.errors.first(), Some(ParseError::InvalidAssignmentTarget { .. })),
+			"`{input}` should not be assignable, got {:?}",
+			parser.errors
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn spans() -> Result<()> {
+	let input = "let x = 1 + foo(2);\nx[0]".chars().collect();
+	let tokenizer = Tokenizer::new(input);
+	let mut parser = Parser::new(tokenizer)?;
+	let program = parser.parse_program();
+	let errors = parser.errors.len();
+
+	assert_eq!(errors, 0, "Parser had {errors} errors: {:#?}", parser.errors);
+	assert_eq!(program.statements.len(), 2);
+
+	let statement = &program.statements[0];
+	assert_eq!(statement.span.range(), 0..19);
+
+	let StatementKind::Let { value, .. } = &statement.kind else {
+		yeet!("Statement was not a let statement ({statement:?})");
+	};
+
+	assert_eq!(value.span.range(), 8..18);
+
+	let ExpressionKind::Infix { lhs, rhs, .. } = &value.kind else {
+		yeet!("Expression was not an infix expression ({value:?})");
+	};
+
+	assert_eq!(lhs.span.range(), 8..9);
+	assert_eq!(rhs.span.range(), 12..18);
+
+	let statement = &program.statements[1];
+	assert_eq!(statement.span.range(), 20..24);
+	assert_eq!((statement.span.start.line, statement.span.start.column), (2, 1));
+
+	Ok(())
+}
+
+#[test]
+fn errors() -> Result<()> {
+	let input = "let = 5; fn(x { x }".chars().collect();
+	let tokenizer = Tokenizer::new(input);
+	let mut parser = Parser::new(tokenizer)?;
+	parser.parse_program();
+
+	let ParseError::UnexpectedToken { got, expected } = &parser.errors[0] else {
+		yeet!("Expected an unexpected token error but got {:?}", parser.errors[0]);
+	};
+
+	assert_eq!(got.kind, TokenKind::Assign);
+	assert_eq!(expected, &["identifier"]);
+	assert_eq!(got.span.range(), 4..5);
+	assert_eq!(parser.errors[0].to_string(), "Unexpected token. Got: Assign, Wanted: identifier");
+
+	assert!(parser.errors.iter().any(|error| matches!(
+		error,
+		ParseError::UnexpectedToken { got, expected }
+			if got.kind == TokenKind::LeftBrace && expected == &["`)`"]
+	)));
+
+	let tokenizer = Tokenizer::new("{1: 2 3}".chars().collect());
+	let mut parser = Parser::new(tokenizer)?;
+	parser.parse_program();
+
+	assert!(matches!(
+		&parser.errors[0],
+		ParseError::UnexpectedToken { expected, .. } if expected == &["`,`", "`}`"]
+	));
+
+	Ok(())
+}
+
+#[test]
+fn incomplete_input() -> Result<()> {
+	let test_cases = [
+		("let add = fn(x, y) {", true),
+		("let add = fn(x, y) { x + y", true),
+		("[1, 2", true),
+		("{\"foo\": 1,", true),
+		("add(1,", true),
+		("1 +", true),
+		("let x =", true),
+		("\"foo", true),
+		("if (x) { 1 } else {", true),
+		("let add = fn(x, y) { x + y };", false),
+		("1 + 1", false),
+		("let = 5", false),
+		("1 + )", false),
+	];
+
+	for (input, expected) in test_cases {
+		let tokenizer = Tokenizer::new(input.chars().collect());
+		let incomplete = match Parser::new(tokenizer) {
+			Ok(mut parser) => {
+				parser.parse_program();
+				parser
+					.errors
+					.iter()
+					.any(ParseError::is_incomplete)
+			}
+			Err(error) => error.is_incomplete(),
+		};
+
+		assert_eq!(incomplete, expected, "Failed on `{input}`");
 	}
 
 	Ok(())
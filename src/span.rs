@@ -0,0 +1,76 @@
+/// A single point in the source code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Location {
+	/// Byte offset into the source.
+	pub offset: usize,
+
+	/// 1-based line number.
+	pub line: usize,
+
+	/// 1-based column number, counted in characters.
+	pub column: usize,
+}
+
+impl Location {
+	/// Moves the location past `char`.
+	pub(crate) fn advance(&mut self, char: char) {
+		self.offset += char.len_utf8();
+
+		if char == '\n' {
+			self.line += 1;
+			self.column = 1;
+		} else {
+			self.column += 1;
+		}
+	}
+}
+
+impl Default for Location {
+	fn default() -> Self {
+		Self { offset: 0, line: 1, column: 1 }
+	}
+}
+
+impl std::fmt::Display for Location {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}:{}", self.line, self.column)
+	}
+}
+
+/// Identifies the piece of source code a span points into, like a file or a single input to the
+/// REPL. See [`crate::diagnostic::Sources`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceId(pub usize);
+
+/// A range in the source code. `start` is inclusive, `end` is exclusive.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+	pub start: Location,
+	pub end: Location,
+
+	/// The source code that `start` and `end` are locations in.
+	pub source: SourceId,
+}
+
+impl Span {
+	pub fn new(start: Location, end: Location, source: SourceId) -> Self {
+		Self { start, end, source }
+	}
+
+	/// Creates a span reaching from the start of `self` to the end of `other`, which has to be in
+	/// the same source.
+	pub fn to(self, other: Self) -> Self {
+		Self { start: self.start, end: other.end, source: self.source }
+	}
+
+	/// The byte range covered by this span.
+	pub fn range(&self) -> std::ops::Range<usize> {
+		self.start.offset..self.end.offset
+	}
+}
+
+impl std::fmt::Display for Span {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}..{}", self.start, self.end)
+	}
+}
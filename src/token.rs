@@ -1,7 +1,33 @@
-use crate::parser::Precedence;
+use {
+	crate::{parser::Precedence, Span},
+	num_bigint::BigInt,
+};
+
+/// A single token together with its location in the source code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+	pub kind: TokenKind,
+	pub span: Span,
+}
+
+impl Token {
+	pub fn new(kind: TokenKind, span: Span) -> Self {
+		Self { kind, span }
+	}
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Token {
+	pub fn precedence(&self) -> Precedence {
+		self.kind.precedence()
+	}
+}
+
+impl std::fmt::Display for Token {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.kind)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
 	/// `=`
 	Assign,
 
@@ -26,18 +52,45 @@ pub enum Token {
 	/// `/`
 	Slash,
 
+	/// `%`
+	Percent,
+
 	/// `<`
 	LessThan,
 
 	/// `>`
 	GreaterThan,
 
+	/// `+=`
+	PlusAssign,
+
+	/// `-=`
+	MinusAssign,
+
+	/// `*=`
+	AsteriskAssign,
+
+	/// `/=`
+	SlashAssign,
+
+	/// `%=`
+	PercentAssign,
+
+	/// `..`
+	DotDot,
+
+	/// `..=`
+	DotDotEq,
+
 	/// `,`
 	Comma,
 
 	/// `:`
 	Colon,
 
+	/// `?`
+	Question,
+
 	/// `;`
 	Semicolon,
 
@@ -62,9 +115,15 @@ pub enum Token {
 	/// The end of the input.
 	Eof,
 
-	/// Any integer.
+	/// Any integer that fits into an `i64`.
 	Int(i64),
 
+	/// Any integer that is too large for [`TokenKind::Int`].
+	BigInt(BigInt),
+
+	/// Any number with a fractional part or an exponent.
+	Float(f64),
+
 	/// Any identifier.
 	Ident(String),
 
@@ -86,6 +145,33 @@ pub enum Token {
 	/// The `return` keyword.
 	Return,
 
+	/// The `while` keyword.
+	While,
+
+	/// The `for` keyword.
+	For,
+
+	/// The `in` keyword.
+	In,
+
+	/// The `break` keyword.
+	Break,
+
+	/// The `continue` keyword.
+	Continue,
+
+	/// The `throw` keyword.
+	Throw,
+
+	/// The `try` keyword.
+	Try,
+
+	/// The `catch` keyword.
+	Catch,
+
+	/// The `finally` keyword.
+	Finally,
+
 	/// The `true` keyword.
 	True,
 
@@ -96,11 +182,15 @@ pub enum Token {
 	Illegal(String),
 }
 
-impl Token {
+impl TokenKind {
 	pub fn int(int: impl Into<i64>) -> Self {
 		Self::Int(int.into())
 	}
 
+	pub fn float(float: impl Into<f64>) -> Self {
+		Self::Float(float.into())
+	}
+
 	pub fn ident(ident: impl Into<String>) -> Self {
 		Self::Ident(ident.into())
 	}
@@ -116,42 +206,74 @@ impl Token {
 	pub fn precedence(&self) -> Precedence {
 		self.into()
 	}
+
+	/// How this kind of token is called in error messages, e.g. `identifier` or `` `)` ``.
+	pub fn describe(&self) -> String {
+		match self {
+			TokenKind::Int(_) | TokenKind::BigInt(_) => String::from("integer"),
+			TokenKind::Float(_) => String::from("float"),
+			TokenKind::Ident(_) => String::from("identifier"),
+			TokenKind::String(_) => String::from("string"),
+			TokenKind::Eof => String::from("end of input"),
+			kind => format!("`{kind}`"),
+		}
+	}
 }
 
-impl std::fmt::Display for Token {
+impl std::fmt::Display for TokenKind {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
-			Token::Assign => write!(f, "="),
-			Token::Equal => write!(f, "=="),
-			Token::NotEqual => write!(f, "!="),
-			Token::Plus => write!(f, "+"),
-			Token::Minus => write!(f, "-"),
-			Token::Bang => write!(f, "!"),
-			Token::Asterisk => write!(f, "*"),
-			Token::Slash => write!(f, "/"),
-			Token::LessThan => write!(f, "<"),
-			Token::GreaterThan => write!(f, ">"),
-			Token::Comma => write!(f, ","),
-			Token::Colon => write!(f, ":"),
-			Token::Semicolon => write!(f, ";"),
-			Token::LeftParen => write!(f, "("),
-			Token::RightParen => write!(f, ")"),
-			Token::LeftBrace => write!(f, "{{"),
-			Token::RightBrace => write!(f, "}}"),
-			Token::LeftBracket => write!(f, "["),
-			Token::RightBracket => write!(f, "]"),
-			Token::Int(int) => write!(f, "{int}"),
-			Token::Ident(ident) => write!(f, "{ident}"),
-			Token::String(string) => write!(f, "\"{string}\""),
-			Token::Function => write!(f, "fn"),
-			Token::Let => write!(f, "let"),
-			Token::If => write!(f, "if"),
-			Token::Else => write!(f, "else"),
-			Token::Return => write!(f, "return"),
-			Token::True => write!(f, "true"),
-			Token::False => write!(f, "false"),
-			Token::Eof => write!(f, "EOF"),
-			Token::Illegal(illegal) => write!(f, "{illegal}"),
+			TokenKind::Assign => write!(f, "="),
+			TokenKind::Equal => write!(f, "=="),
+			TokenKind::NotEqual => write!(f, "!="),
+			TokenKind::Plus => write!(f, "+"),
+			TokenKind::Minus => write!(f, "-"),
+			TokenKind::Bang => write!(f, "!"),
+			TokenKind::Asterisk => write!(f, "*"),
+			TokenKind::Slash => write!(f, "/"),
+			TokenKind::Percent => write!(f, "%"),
+			TokenKind::LessThan => write!(f, "<"),
+			TokenKind::GreaterThan => write!(f, ">"),
+			TokenKind::PlusAssign => write!(f, "+="),
+			TokenKind::MinusAssign => write!(f, "-="),
+			TokenKind::AsteriskAssign => write!(f, "*="),
+			TokenKind::SlashAssign => write!(f, "/="),
+			TokenKind::PercentAssign => write!(f, "%="),
+			TokenKind::DotDot => write!(f, ".."),
+			TokenKind::DotDotEq => write!(f, "..="),
+			TokenKind::Comma => write!(f, ","),
+			TokenKind::Colon => write!(f, ":"),
+			TokenKind::Question => write!(f, "?"),
+			TokenKind::Semicolon => write!(f, ";"),
+			TokenKind::LeftParen => write!(f, "("),
+			TokenKind::RightParen => write!(f, ")"),
+			TokenKind::LeftBrace => write!(f, "{{"),
+			TokenKind::RightBrace => write!(f, "}}"),
+			TokenKind::LeftBracket => write!(f, "["),
+			TokenKind::RightBracket => write!(f, "]"),
+			TokenKind::Int(int) => write!(f, "{int}"),
+			TokenKind::BigInt(int) => write!(f, "{int}"),
+			TokenKind::Float(float) => write!(f, "{float:?}"),
+			TokenKind::Ident(ident) => write!(f, "{ident}"),
+			TokenKind::String(string) => write!(f, "\"{string}\""),
+			TokenKind::Function => write!(f, "fn"),
+			TokenKind::Let => write!(f, "let"),
+			TokenKind::If => write!(f, "if"),
+			TokenKind::Else => write!(f, "else"),
+			TokenKind::Return => write!(f, "return"),
+			TokenKind::While => write!(f, "while"),
+			TokenKind::For => write!(f, "for"),
+			TokenKind::In => write!(f, "in"),
+			TokenKind::Break => write!(f, "break"),
+			TokenKind::Continue => write!(f, "continue"),
+			TokenKind::Throw => write!(f, "throw"),
+			TokenKind::Try => write!(f, "try"),
+			TokenKind::Catch => write!(f, "catch"),
+			TokenKind::Finally => write!(f, "finally"),
+			TokenKind::True => write!(f, "true"),
+			TokenKind::False => write!(f, "false"),
+			TokenKind::Eof => write!(f, "EOF"),
+			TokenKind::Illegal(illegal) => write!(f, "{illegal}"),
 		}
 	}
 }
@@ -0,0 +1,26 @@
+use crate::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenizeError {
+	/// A string literal was never closed.
+	UnterminatedString { span: Span },
+}
+
+impl TokenizeError {
+	pub fn span(&self) -> Span {
+		match self {
+			TokenizeError::UnterminatedString { span } => *span,
+		}
+	}
+}
+
+impl std::fmt::Display for TokenizeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			TokenizeError::UnterminatedString { .. } => write!(f, "Unterminated string literal"),
+		}
+	}
+}
+
+impl std::error::Error for TokenizeError {
+}
@@ -1,73 +1,138 @@
-use {
-	crate::{record, Token},
-	color_eyre::{eyre::Context, Result},
-};
+use crate::{record, span::Location, yeet, SourceId, Span, Token, TokenKind};
 
 #[cfg(test)]
 mod tests;
 
+mod error;
+pub use error::TokenizeError;
+
 pub struct Tokenizer {
 	pub(crate) input: Vec<char>,
 	pub(crate) position: usize,
 	pub(crate) read_position: usize,
 	pub(crate) char: char,
+	pub(crate) location: Location,
+	pub(crate) source: SourceId,
 }
 
 /// public methods
 impl Tokenizer {
 	#[tracing::instrument(level = "TRACE", ret)]
 	pub fn new(input: Vec<char>) -> Self {
-		let mut tokenizer = Self { input, position: 0, read_position: 0, char: '\0' };
+		let mut tokenizer = Self {
+			input,
+			position: 0,
+			read_position: 0,
+			char: '\0',
+			location: Location::default(),
+			source: SourceId::default(),
+		};
+
 		tokenizer.next_char();
 		tokenizer
 	}
 
+	/// Marks the spans of all tokens as pointing into `source`, for inputs that are tracked in
+	/// [`crate::diagnostic::Sources`].
+	pub fn with_source(mut self, source: SourceId) -> Self {
+		self.source = source;
+		self
+	}
+
 	#[tracing::instrument(level = "DEBUG", ret)]
-	pub fn step(&mut self) -> Result<Token> {
+	pub fn step(&mut self) -> Result<Token, TokenizeError> {
 		self.skip_whitespace();
 
-		let token = match self.char {
+		let start = self.location;
+		let kind = match self.char {
 			'=' if matches!(self.peek_char(), Some('=')) => {
 				self.next_char();
-				Token::Equal
+				TokenKind::Equal
 			}
-			'=' => Token::Assign,
-			'+' => Token::Plus,
-			'-' => Token::Minus,
+			'=' => TokenKind::Assign,
+			'+' if matches!(self.peek_char(), Some('=')) => {
+				self.next_char();
+				TokenKind::PlusAssign
+			}
+			'+' => TokenKind::Plus,
+			'-' if matches!(self.peek_char(), Some('=')) => {
+				self.next_char();
+				TokenKind::MinusAssign
+			}
+			'-' => TokenKind::Minus,
 			'!' if matches!(self.peek_char(), Some('=')) => {
 				self.next_char();
-				Token::NotEqual
+				TokenKind::NotEqual
+			}
+			'!' => TokenKind::Bang,
+			'*' if matches!(self.peek_char(), Some('=')) => {
+				self.next_char();
+				TokenKind::AsteriskAssign
 			}
-			'!' => Token::Bang,
-			'*' => Token::Asterisk,
-			'/' => Token::Slash,
-			'<' => Token::LessThan,
-			'>' => Token::GreaterThan,
-			',' => Token::Comma,
-			':' => Token::Colon,
-			';' => Token::Semicolon,
-			'(' => Token::LeftParen,
-			')' => Token::RightParen,
-			'{' => Token::LeftBrace,
-			'}' => Token::RightBrace,
-			'[' => Token::LeftBracket,
-			']' => Token::RightBracket,
-			'\0' => Token::Eof,
-			'"' => self.read_string(),
-			_ if self.is_digit() => return self.read_integer(),
-			_ if self.is_letter() => return Ok(self.read_identifier()),
-			char => Token::illegal(char),
+			'*' => TokenKind::Asterisk,
+			'/' if matches!(self.peek_char(), Some('=')) => {
+				self.next_char();
+				TokenKind::SlashAssign
+			}
+			'/' => TokenKind::Slash,
+			'%' if matches!(self.peek_char(), Some('=')) => {
+				self.next_char();
+				TokenKind::PercentAssign
+			}
+			'%' => TokenKind::Percent,
+			'<' => TokenKind::LessThan,
+			'>' => TokenKind::GreaterThan,
+			'.' if matches!(self.peek_char(), Some('.')) => {
+				self.next_char();
+
+				if matches!(self.peek_char(), Some('=')) {
+					self.next_char();
+					TokenKind::DotDotEq
+				} else {
+					TokenKind::DotDot
+				}
+			}
+			',' => TokenKind::Comma,
+			':' => TokenKind::Colon,
+			'?' => TokenKind::Question,
+			';' => TokenKind::Semicolon,
+			'(' => TokenKind::LeftParen,
+			')' => TokenKind::RightParen,
+			'{' => TokenKind::LeftBrace,
+			'}' => TokenKind::RightBrace,
+			'[' => TokenKind::LeftBracket,
+			']' => TokenKind::RightBracket,
+			'\0' => TokenKind::Eof,
+			'"' => self.read_string(start)?,
+			_ if self.is_digit() => {
+				let number = self.read_number();
+				return Ok(self.token(number, start));
+			}
+			_ if self.is_letter() => {
+				let ident = self.read_identifier();
+				return Ok(self.token(ident, start));
+			}
+			char => TokenKind::illegal(char),
 		};
 
 		self.next_char();
-		Ok(token)
+		Ok(self.token(kind, start))
 	}
 }
 
 /// private methods
 impl Tokenizer {
+	/// Creates a token spanning from `start` to the current location.
+	fn token(&self, kind: TokenKind, start: Location) -> Token {
+		Token::new(kind, Span::new(start, self.location, self.source))
+	}
+
 	#[tracing::instrument(level = "DEBUG", fields(reading))]
 	fn next_char(&mut self) {
+		if self.read_position > 0 && self.position < self.input.len() {
+			self.location.advance(self.char);
+		}
+
 		match record!("reading", self.input.get(self.read_position)) {
 			// We either just started parsing or we are done.
 			None => self.char = '\0',
@@ -84,7 +149,7 @@ impl Tokenizer {
 	}
 
 	#[tracing::instrument(level = "TRACE", ret)]
-	fn read_identifier(&mut self) -> Token {
+	fn read_identifier(&mut self) -> TokenKind {
 		let position = self.position;
 
 		while self.is_letter() {
@@ -94,44 +159,102 @@ impl Tokenizer {
 		let ident = String::from_iter(&self.input[position..self.position]);
 
 		match ident.as_str() {
-			"fn" => Token::Function,
-			"let" => Token::Let,
-			"if" => Token::If,
-			"else" => Token::Else,
-			"return" => Token::Return,
-			"true" => Token::True,
-			"false" => Token::False,
-			_ => Token::Ident(ident),
+			"fn" => TokenKind::Function,
+			"let" => TokenKind::Let,
+			"if" => TokenKind::If,
+			"else" => TokenKind::Else,
+			"return" => TokenKind::Return,
+			"while" => TokenKind::While,
+			"for" => TokenKind::For,
+			"in" => TokenKind::In,
+			"break" => TokenKind::Break,
+			"continue" => TokenKind::Continue,
+			"throw" => TokenKind::Throw,
+			"try" => TokenKind::Try,
+			"catch" => TokenKind::Catch,
+			"finally" => TokenKind::Finally,
+			"true" => TokenKind::True,
+			"false" => TokenKind::False,
+			_ => TokenKind::Ident(ident),
 		}
 	}
 
 	#[tracing::instrument(level = "TRACE", ret)]
-	fn read_string(&mut self) -> Token {
+	fn read_string(&mut self, start: Location) -> Result<TokenKind, TokenizeError> {
 		let position = self.position + 1;
 
 		loop {
 			self.next_char();
-			if matches!(self.char, '"' | '\0') {
+
+			if self.char == '"' {
 				break;
 			}
+
+			if self.position >= self.input.len() {
+				yeet!(TokenizeError::UnterminatedString {
+					span: Span::new(start, self.location, self.source)
+				});
+			}
 		}
 
-		Token::String(String::from_iter(&self.input[position..self.position]))
+		Ok(TokenKind::String(String::from_iter(&self.input[position..self.position])))
 	}
 
+	/// Reads an integer, or a float if the digits are followed by a fractional part (`1.5`) or an
+	/// exponent (`1e3`, `2.5E-4`).
 	#[tracing::instrument(level = "TRACE", ret)]
-	fn read_integer(&mut self) -> Result<Token> {
+	fn read_number(&mut self) -> TokenKind {
 		let position = self.position;
+		let mut is_float = false;
 
-		while self.is_digit() {
+		self.skip_digits();
+
+		// A dot that isn't followed by a digit belongs to the next token.
+		if self.char == '.'
+			&& self
+				.peek_char()
+				.is_some_and(|char| char.is_ascii_digit())
+		{
+			is_float = true;
 			self.next_char();
+			self.skip_digits();
+		}
+
+		if matches!(self.char, 'e' | 'E') {
+			let sign = matches!(self.peek_char(), Some('+' | '-')) as usize;
+			let digit = self.input.get(self.read_position + sign);
+
+			if digit.is_some_and(|char| char.is_ascii_digit()) {
+				is_float = true;
+
+				for _ in 0..=sign {
+					self.next_char();
+				}
+
+				self.skip_digits();
+			}
 		}
 
-		let int = String::from_iter(&self.input[position..self.position])
-			.parse::<i64>()
-			.context("Failed to parse integer")?;
+		let literal = String::from_iter(&self.input[position..self.position]);
+
+		if is_float {
+			// Anything we accepted above is valid float syntax.
+			let float = literal.parse::<f64>().unwrap_or(f64::INFINITY);
+			return TokenKind::Float(float);
+		}
 
-		Ok(Token::Int(int))
+		match literal.parse::<i64>() {
+			Ok(int) => TokenKind::Int(int),
+			// The literal consists of nothing but digits, so it's always a valid big integer.
+			Err(_) => TokenKind::BigInt(literal.parse().unwrap_or_default()),
+		}
+	}
+
+	#[tracing::instrument(level = "TRACE")]
+	fn skip_digits(&mut self) {
+		while self.is_digit() {
+			self.next_char();
+		}
 	}
 
 	#[tracing::instrument(level = "TRACE")]
@@ -148,13 +271,16 @@ impl Tokenizer {
 
 	#[tracing::instrument(level = "TRACE", ret)]
 	fn is_digit(&self) -> bool {
-		self.char.is_digit(10)
+		self.char.is_ascii_digit()
 	}
 }
 
 impl std::fmt::Debug for Tokenizer {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		let Tokenizer { position, read_position, char, .. } = self;
-		write!(f, "{{ position: {position}, read_position: {read_position}, char: `{char}` }}")
+		let Tokenizer { position, read_position, char, location, .. } = self;
+		write!(
+			f,
+			"{{ position: {position}, read_position: {read_position}, char: `{char}`, location: {location} }}"
+		)
 	}
 }
@@ -1,6 +1,7 @@
 use {
-	crate::{Token, Tokenizer},
+	crate::{TokenKind, TokenizeError, Tokenizer},
 	color_eyre::Result,
+	num_bigint::BigInt,
 	pretty_assertions::assert_eq,
 };
 
@@ -9,19 +10,19 @@ fn simple() -> Result<()> {
 	let input = "=+(){},;".chars().collect();
 	let mut tokenizer = Tokenizer::new(input);
 	let expected = [
-		Token::Assign,
-		Token::Plus,
-		Token::LeftParen,
-		Token::RightParen,
-		Token::LeftBrace,
-		Token::RightBrace,
-		Token::Comma,
-		Token::Semicolon,
-		Token::Eof,
+		TokenKind::Assign,
+		TokenKind::Plus,
+		TokenKind::LeftParen,
+		TokenKind::RightParen,
+		TokenKind::LeftBrace,
+		TokenKind::RightBrace,
+		TokenKind::Comma,
+		TokenKind::Semicolon,
+		TokenKind::Eof,
 	];
 
 	for expected in expected {
-		assert_eq!(expected, tokenizer.step()?);
+		assert_eq!(expected, tokenizer.step()?.kind);
 	}
 
 	Ok(())
@@ -44,47 +45,47 @@ fn basic_program() -> Result<()> {
 
 	let mut tokenizer = Tokenizer::new(input);
 	let expected = [
-		Token::Let,
-		Token::ident("five"),
-		Token::Assign,
-		Token::Int(5),
-		Token::Semicolon,
-		Token::Let,
-		Token::ident("ten"),
-		Token::Assign,
-		Token::Int(10),
-		Token::Semicolon,
-		Token::Let,
-		Token::ident("add"),
-		Token::Assign,
-		Token::Function,
-		Token::LeftParen,
-		Token::ident("x"),
-		Token::Comma,
-		Token::ident("y"),
-		Token::RightParen,
-		Token::LeftBrace,
-		Token::ident("x"),
-		Token::Plus,
-		Token::ident("y"),
-		Token::Semicolon,
-		Token::RightBrace,
-		Token::Semicolon,
-		Token::Let,
-		Token::ident("result"),
-		Token::Assign,
-		Token::ident("add"),
-		Token::LeftParen,
-		Token::ident("five"),
-		Token::Comma,
-		Token::ident("ten"),
-		Token::RightParen,
-		Token::Semicolon,
-		Token::Eof,
+		TokenKind::Let,
+		TokenKind::ident("five"),
+		TokenKind::Assign,
+		TokenKind::Int(5),
+		TokenKind::Semicolon,
+		TokenKind::Let,
+		TokenKind::ident("ten"),
+		TokenKind::Assign,
+		TokenKind::Int(10),
+		TokenKind::Semicolon,
+		TokenKind::Let,
+		TokenKind::ident("add"),
+		TokenKind::Assign,
+		TokenKind::Function,
+		TokenKind::LeftParen,
+		TokenKind::ident("x"),
+		TokenKind::Comma,
+		TokenKind::ident("y"),
+		TokenKind::RightParen,
+		TokenKind::LeftBrace,
+		TokenKind::ident("x"),
+		TokenKind::Plus,
+		TokenKind::ident("y"),
+		TokenKind::Semicolon,
+		TokenKind::RightBrace,
+		TokenKind::Semicolon,
+		TokenKind::Let,
+		TokenKind::ident("result"),
+		TokenKind::Assign,
+		TokenKind::ident("add"),
+		TokenKind::LeftParen,
+		TokenKind::ident("five"),
+		TokenKind::Comma,
+		TokenKind::ident("ten"),
+		TokenKind::RightParen,
+		TokenKind::Semicolon,
+		TokenKind::Eof,
 	];
 
 	for expected in expected {
-		assert_eq!(expected, tokenizer.step()?);
+		assert_eq!(expected, tokenizer.step()?.kind);
 	}
 
 	Ok(())
@@ -101,7 +102,7 @@ fn more_tokens() -> Result<()> {
 		};
 
 		let result = add(five, ten);
-		!-/*5;
+		!-/*%5;
 		5 < 10 > 5;
 
 		if (5 < 10) {
@@ -122,97 +123,285 @@ fn more_tokens() -> Result<()> {
 
 	let mut tokenizer = Tokenizer::new(input);
 	let expected = [
-		Token::Let,
-		Token::ident("five"),
-		Token::Assign,
-		Token::Int(5),
-		Token::Semicolon,
-		Token::Let,
-		Token::ident("ten"),
-		Token::Assign,
-		Token::Int(10),
-		Token::Semicolon,
-		Token::Let,
-		Token::ident("add"),
-		Token::Assign,
-		Token::Function,
-		Token::LeftParen,
-		Token::ident("x"),
-		Token::Comma,
-		Token::ident("y"),
-		Token::RightParen,
-		Token::LeftBrace,
-		Token::ident("x"),
-		Token::Plus,
-		Token::ident("y"),
-		Token::Semicolon,
-		Token::RightBrace,
-		Token::Semicolon,
-		Token::Let,
-		Token::ident("result"),
-		Token::Assign,
-		Token::ident("add"),
-		Token::LeftParen,
-		Token::ident("five"),
-		Token::Comma,
-		Token::ident("ten"),
-		Token::RightParen,
-		Token::Semicolon,
-		Token::Bang,
-		Token::Minus,
-		Token::Slash,
-		Token::Asterisk,
-		Token::Int(5),
-		Token::Semicolon,
-		Token::Int(5),
-		Token::LessThan,
-		Token::Int(10),
-		Token::GreaterThan,
-		Token::Int(5),
-		Token::Semicolon,
-		Token::If,
-		Token::LeftParen,
-		Token::Int(5),
-		Token::LessThan,
-		Token::Int(10),
-		Token::RightParen,
-		Token::LeftBrace,
-		Token::Return,
-		Token::True,
-		Token::Semicolon,
-		Token::RightBrace,
-		Token::Else,
-		Token::LeftBrace,
-		Token::Return,
-		Token::False,
-		Token::Semicolon,
-		Token::RightBrace,
-		Token::Int(10),
-		Token::Equal,
-		Token::Int(10),
-		Token::Semicolon,
-		Token::Int(10),
-		Token::NotEqual,
-		Token::Int(9),
-		Token::Semicolon,
-		Token::string("foobar"),
-		Token::string("foo bar"),
-		Token::LeftBracket,
-		Token::Int(1),
-		Token::Comma,
-		Token::Int(2),
-		Token::RightBracket,
-		Token::Semicolon,
-		Token::LeftBrace,
-		Token::string("foo"),
-		Token::Colon,
-		Token::string("bar"),
-		Token::RightBrace,
-		Token::Eof,
+		TokenKind::Let,
+		TokenKind::ident("five"),
+		TokenKind::Assign,
+		TokenKind::Int(5),
+		TokenKind::Semicolon,
+		TokenKind::Let,
+		TokenKind::ident("ten"),
+		TokenKind::Assign,
+		TokenKind::Int(10),
+		TokenKind::Semicolon,
+		TokenKind::Let,
+		TokenKind::ident("add"),
+		TokenKind::Assign,
+		TokenKind::Function,
+		TokenKind::LeftParen,
+		TokenKind::ident("x"),
+		TokenKind::Comma,
+		TokenKind::ident("y"),
+		TokenKind::RightParen,
+		TokenKind::LeftBrace,
+		TokenKind::ident("x"),
+		TokenKind::Plus,
+		TokenKind::ident("y"),
+		TokenKind::Semicolon,
+		TokenKind::RightBrace,
+		TokenKind::Semicolon,
+		TokenKind::Let,
+		TokenKind::ident("result"),
+		TokenKind::Assign,
+		TokenKind::ident("add"),
+		TokenKind::LeftParen,
+		TokenKind::ident("five"),
+		TokenKind::Comma,
+		TokenKind::ident("ten"),
+		TokenKind::RightParen,
+		TokenKind::Semicolon,
+		TokenKind::Bang,
+		TokenKind::Minus,
+		TokenKind::Slash,
+		TokenKind::Asterisk,
+		TokenKind::Percent,
+		TokenKind::Int(5),
+		TokenKind::Semicolon,
+		TokenKind::Int(5),
+		TokenKind::LessThan,
+		TokenKind::Int(10),
+		TokenKind::GreaterThan,
+		TokenKind::Int(5),
+		TokenKind::Semicolon,
+		TokenKind::If,
+		TokenKind::LeftParen,
+		TokenKind::Int(5),
+		TokenKind::LessThan,
+		TokenKind::Int(10),
+		TokenKind::RightParen,
+		TokenKind::LeftBrace,
+		TokenKind::Return,
+		TokenKind::True,
+		TokenKind::Semicolon,
+		TokenKind::RightBrace,
+		TokenKind::Else,
+		TokenKind::LeftBrace,
+		TokenKind::Return,
+		TokenKind::False,
+		TokenKind::Semicolon,
+		TokenKind::RightBrace,
+		TokenKind::Int(10),
+		TokenKind::Equal,
+		TokenKind::Int(10),
+		TokenKind::Semicolon,
+		TokenKind::Int(10),
+		TokenKind::NotEqual,
+		TokenKind::Int(9),
+		TokenKind::Semicolon,
+		TokenKind::string("foobar"),
+		TokenKind::string("foo bar"),
+		TokenKind::LeftBracket,
+		TokenKind::Int(1),
+		TokenKind::Comma,
+		TokenKind::Int(2),
+		TokenKind::RightBracket,
+		TokenKind::Semicolon,
+		TokenKind::LeftBrace,
+		TokenKind::string("foo"),
+		TokenKind::Colon,
+		TokenKind::string("bar"),
+		TokenKind::RightBrace,
+		TokenKind::Eof,
 	];
 
 	for expected in expected {
-		assert_eq!(expected, tokenizer.step()?);
+		assert_eq!(expected, tokenizer.step()?.kind);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn spans() -> Result<()> {
+	let input = "let x = 5;\n\"foo\" == ä".chars().collect();
+	let mut tokenizer = Tokenizer::new(input);
+	let expected = [
+		(TokenKind::Let, (0, 1, 1), (3, 1, 4)),
+		(TokenKind::ident("x"), (4, 1, 5), (5, 1, 6)),
+		(TokenKind::Assign, (6, 1, 7), (7, 1, 8)),
+		(TokenKind::Int(5), (8, 1, 9), (9, 1, 10)),
+		(TokenKind::Semicolon, (9, 1, 10), (10, 1, 11)),
+		(TokenKind::string("foo"), (11, 2, 1), (16, 2, 6)),
+		(TokenKind::Equal, (17, 2, 7), (19, 2, 9)),
+		(TokenKind::ident("ä"), (20, 2, 10), (22, 2, 11)),
+		(TokenKind::Eof, (22, 2, 11), (22, 2, 11)),
+	];
+
+	for (kind, start, end) in expected {
+		let token = tokenizer.step()?;
+		let span = token.span;
+
+		assert_eq!(token.kind, kind);
+		assert_eq!((span.start.offset, span.start.line, span.start.column), start);
+		assert_eq!((span.end.offset, span.end.line, span.end.column), end);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn errors() -> Result<()> {
+	let mut tokenizer = Tokenizer::new("\"foo".chars().collect());
+	let error = tokenizer.step().unwrap_err();
+	assert!(matches!(error, TokenizeError::UnterminatedString { .. }));
+	assert_eq!(error.span().range(), 0..4);
+
+	Ok(())
+}
+
+#[test]
+fn assignment_operators() -> Result<()> {
+	let input = "x = 1; x += 2; x -= 3; x *= 4; x /= 5; x %= 6; x == 7";
+	let mut tokenizer = Tokenizer::new(input.chars().collect());
+
+	let operators = [
+		TokenKind::Assign,
+		TokenKind::PlusAssign,
+		TokenKind::MinusAssign,
+		TokenKind::AsteriskAssign,
+		TokenKind::SlashAssign,
+		TokenKind::PercentAssign,
+		TokenKind::Equal,
+	];
+
+	for (n, operator) in operators.into_iter().enumerate() {
+		assert_eq!(tokenizer.step()?.kind, TokenKind::ident("x"));
+		assert_eq!(tokenizer.step()?.kind, operator);
+		assert_eq!(tokenizer.step()?.kind, TokenKind::Int(n as i64 + 1));
+		assert_eq!(
+			tokenizer.step()?.kind,
+			if n < 6 { TokenKind::Semicolon } else { TokenKind::Eof }
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn loops() -> Result<()> {
+	let input = "for (k, v in 0..=10) { while (x) { break; continue } } 1..2";
+	let mut tokenizer = Tokenizer::new(input.chars().collect());
+
+	let expected = [
+		TokenKind::For,
+		TokenKind::LeftParen,
+		TokenKind::ident("k"),
+		TokenKind::Comma,
+		TokenKind::ident("v"),
+		TokenKind::In,
+		TokenKind::Int(0),
+		TokenKind::DotDotEq,
+		TokenKind::Int(10),
+		TokenKind::RightParen,
+		TokenKind::LeftBrace,
+		TokenKind::While,
+		TokenKind::LeftParen,
+		TokenKind::ident("x"),
+		TokenKind::RightParen,
+		TokenKind::LeftBrace,
+		TokenKind::Break,
+		TokenKind::Semicolon,
+		TokenKind::Continue,
+		TokenKind::RightBrace,
+		TokenKind::RightBrace,
+		TokenKind::Int(1),
+		TokenKind::DotDot,
+		TokenKind::Int(2),
+		TokenKind::Eof,
+	];
+
+	for kind in expected {
+		assert_eq!(tokenizer.step()?.kind, kind);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn exceptions() -> Result<()> {
+	let input = "try { throw e } catch (e) { } finally { f()? }";
+	let mut tokenizer = Tokenizer::new(input.chars().collect());
+
+	let expected = [
+		TokenKind::Try,
+		TokenKind::LeftBrace,
+		TokenKind::Throw,
+		TokenKind::ident("e"),
+		TokenKind::RightBrace,
+		TokenKind::Catch,
+		TokenKind::LeftParen,
+		TokenKind::ident("e"),
+		TokenKind::RightParen,
+		TokenKind::LeftBrace,
+		TokenKind::RightBrace,
+		TokenKind::Finally,
+		TokenKind::LeftBrace,
+		TokenKind::ident("f"),
+		TokenKind::LeftParen,
+		TokenKind::RightParen,
+		TokenKind::Question,
+		TokenKind::RightBrace,
+		TokenKind::Eof,
+	];
+
+	for kind in expected {
+		assert_eq!(tokenizer.step()?.kind, kind);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn floats() -> Result<()> {
+	let input = "3.25 1e3 2.5E-4 7e+2 1.0 1. 0..5 2e";
+	let mut tokenizer = Tokenizer::new(input.chars().collect());
+
+	let expected = [
+		TokenKind::float(3.25),
+		TokenKind::float(1000.0),
+		TokenKind::float(0.00025),
+		TokenKind::float(700.0),
+		TokenKind::float(1.0),
+		TokenKind::Int(1),
+		TokenKind::illegal("."),
+		TokenKind::Int(0),
+		TokenKind::DotDot,
+		TokenKind::Int(5),
+		TokenKind::Int(2),
+		TokenKind::ident("e"),
+		TokenKind::Eof,
+	];
+
+	for kind in expected {
+		assert_eq!(tokenizer.step()?.kind, kind);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn big_integers() -> Result<()> {
+	let input = "9223372036854775807 9223372036854775808 123456789012345678901234567890";
+	let mut tokenizer = Tokenizer::new(input.chars().collect());
+
+	let expected = [
+		TokenKind::Int(i64::MAX),
+		TokenKind::BigInt(BigInt::from(i64::MAX) + 1),
+		TokenKind::BigInt("123456789012345678901234567890".parse()?),
+		TokenKind::Eof,
+	];
+
+	for kind in expected {
+		assert_eq!(tokenizer.step()?.kind, kind);
 	}
 
 	Ok(())
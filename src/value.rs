@@ -3,36 +3,225 @@ use {
 		eval::{builtins::BuiltinFunction, Environment},
 		Statement,
 	},
-	std::{collections::HashMap, rc::Rc},
+	num_bigint::BigInt,
+	std::{cmp::Ordering, collections::HashMap, rc::Rc},
 };
 
 #[derive(Debug, Clone)]
 pub enum Value {
 	Null,
-	Return(Box<Value>),
 	Int(i64),
+	/// An integer that doesn't fit into an `i64`. Anything smaller is always stored as `Int`, see
+	/// the `From<BigInt>` impl.
+	BigInt(BigInt),
+	Float(f64),
 	Bool(bool),
 	String(String),
 	Array(Vec<Value>),
-	Map(HashMap<String, Value>),
-	Function { parameters: Vec<String>, body: Vec<Statement>, environment: Environment },
+	Map(HashMap<MapKey, Value>),
+	/// `start..end`, or `start..=end` if `inclusive`. The numbers in between are only produced
+	/// while iterating.
+	Range {
+		start: i64,
+		end: i64,
+		inclusive: bool,
+	},
+	/// Shared, so that every copy of a function is the same function. See [`Function`].
+	Function(Rc<Function>),
 	BuiltinFunction(Rc<dyn BuiltinFunction>),
+	/// Something that went wrong, returned instead of a result. Unlike a thrown error it is an
+	/// ordinary value, which `?` passes on to the caller.
+	Error(Box<Value>),
+}
+
+/// A value that can be used as a map key. Floats, and everything containing them, can't be
+/// hashed, so only integers, booleans, strings and arrays of those can.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+	Int(i64),
+	BigInt(BigInt),
+	Bool(bool),
+	String(String),
+	Array(Vec<MapKey>),
+}
+
+impl MapKey {
+	/// Converts `value` into a key, or returns `None` if it can't be hashed.
+	pub fn new(value: Value) -> Option<Self> {
+		Some(match value {
+			Value::Int(int) => MapKey::Int(int),
+			Value::BigInt(int) => MapKey::BigInt(int),
+			Value::Bool(bool) => MapKey::Bool(bool),
+			Value::String(string) => MapKey::String(string),
+			Value::Array(array) => MapKey::Array(
+				array
+					.into_iter()
+					.map(MapKey::new)
+					.collect::<Option<_>>()?,
+			),
+			_ => return None,
+		})
+	}
+
+	/// Where keys of this kind go when sorted next to keys of another kind.
+	fn rank(&self) -> u8 {
+		match self {
+			MapKey::Int(_) | MapKey::BigInt(_) => 0,
+			MapKey::Bool(_) => 1,
+			MapKey::String(_) => 2,
+			MapKey::Array(_) => 3,
+		}
+	}
+}
+
+impl Ord for MapKey {
+	/// Integers are ordered by value, no matter whether they are big or not.
+	fn cmp(&self, other: &Self) -> Ordering {
+		match (self, other) {
+			(MapKey::Int(left), MapKey::Int(right)) => left.cmp(right),
+			(MapKey::Int(left), MapKey::BigInt(right)) => BigInt::from(*left).cmp(right),
+			(MapKey::BigInt(left), MapKey::Int(right)) => left.cmp(&BigInt::from(*right)),
+			(MapKey::BigInt(left), MapKey::BigInt(right)) => left.cmp(right),
+			(MapKey::Bool(left), MapKey::Bool(right)) => left.cmp(right),
+			(MapKey::String(left), MapKey::String(right)) => left.cmp(right),
+			(MapKey::Array(left), MapKey::Array(right)) => left.cmp(right),
+			_ => self.rank().cmp(&other.rank()),
+		}
+	}
+}
+
+impl PartialOrd for MapKey {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl From<&str> for MapKey {
+	fn from(key: &str) -> Self {
+		MapKey::String(key.to_owned())
+	}
+}
+
+impl From<MapKey> for Value {
+	fn from(key: MapKey) -> Self {
+		match key {
+			MapKey::Int(int) => Value::Int(int),
+			MapKey::BigInt(int) => Value::BigInt(int),
+			MapKey::Bool(bool) => Value::Bool(bool),
+			MapKey::String(string) => Value::String(string),
+			MapKey::Array(array) => Value::Array(array.into_iter().map(Value::from).collect()),
+		}
+	}
+}
+
+impl std::fmt::Display for MapKey {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			MapKey::Int(int) => write!(f, "{int}"),
+			MapKey::BigInt(int) => write!(f, "{int}"),
+			MapKey::Bool(bool) => write!(f, "{bool}"),
+			MapKey::String(string) => write!(f, "{string}"),
+			MapKey::Array(keys) => {
+				write!(
+					f,
+					"[{}]",
+					keys.iter()
+						.map(|key| key.to_string())
+						.collect::<Vec<_>>()
+						.join(", ")
+				)
+			}
+		}
+	}
+}
+
+/// A function defined in Monkey code, created anew every time a `fn` expression is evaluated.
+pub struct Function {
+	/// The name the function was bound to when it was defined with `let`, used in backtraces.
+	pub name: Option<String>,
+	pub parameters: Vec<String>,
+	pub body: Vec<Statement>,
+	/// The scope the function was defined in. It is captured by reference, so the function sees
+	/// any later changes to it, including the binding of the function itself.
+	pub environment: Rc<Environment>,
+}
+
+impl std::fmt::Debug for Function {
+	/// Leaves out the environment, which usually contains the function itself.
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Function")
+			.field("name", &self.name)
+			.field("parameters", &self.parameters)
+			.field("body", &self.body)
+			.finish_non_exhaustive()
+	}
+}
+
+impl Value {
+	/// An error value with `message` as its payload.
+	pub fn error(message: impl Into<String>) -> Self {
+		Value::Error(Box::new(Value::String(message.into())))
+	}
+
+	/// A human readable name for the type of this value.
+	pub fn type_name(&self) -> &'static str {
+		match self {
+			Value::Null => "null",
+			Value::Int(_) | Value::BigInt(_) => "int",
+			Value::Float(_) => "float",
+			Value::Bool(_) => "bool",
+			Value::String(_) => "string",
+			Value::Array(_) => "array",
+			Value::Map(_) => "map",
+			Value::Range { .. } => "range",
+			Value::Function(_) => "function",
+			Value::BuiltinFunction(_) => "builtin function",
+			Value::Error(_) => "error",
+		}
+	}
+}
+
+impl Value {
+	/// Whether the value counts as true in a condition, or `None` if it can't be used as one.
+	pub fn truthiness(&self) -> Option<bool> {
+		match self {
+			Value::Bool(bool) => Some(*bool),
+			Value::Int(int) => Some(*int != 0),
+			Value::BigInt(_) => Some(true),
+			Value::Float(float) => Some(*float != 0.0),
+			_ => None,
+		}
+	}
+}
+
+impl From<BigInt> for Value {
+	/// Demotes `int` to a regular `Int` if it's small enough.
+	fn from(int: BigInt) -> Self {
+		match i64::try_from(&int) {
+			Ok(int) => Value::Int(int),
+			Err(_) => Value::BigInt(int),
+		}
+	}
 }
 
 impl PartialEq for Value {
 	fn eq(&self, other: &Self) -> bool {
 		match (self, other) {
 			(Value::Null, Value::Null) => true,
-			(Value::Return(left), Value::Return(right)) => left == right,
 			(Value::Int(left), Value::Int(right)) => left == right,
+			(Value::BigInt(left), Value::BigInt(right)) => left == right,
+			(Value::Float(left), Value::Float(right)) => left == right,
 			(Value::Bool(left), Value::Bool(right)) => left == right,
 			(Value::String(left), Value::String(right)) => left == right,
 			(Value::Array(left), Value::Array(right)) => left == right,
 			(Value::Map(left), Value::Map(right)) => left == right,
 			(
-				Value::Function { environment: environment1, .. },
-				Value::Function { environment: environment2, .. },
-			) => environment1 == environment2,
+				Value::Range { start, end, inclusive },
+				Value::Range { start: start2, end: end2, inclusive: inclusive2 },
+			) => (start, end, inclusive) == (start2, end2, inclusive2),
+			// Functions are only equal to themselves, no matter what they look like.
+			(Value::Function(left), Value::Function(right)) => Rc::ptr_eq(left, right),
+			(Value::Error(left), Value::Error(right)) => left == right,
 			_ => false,
 		}
 	}
@@ -42,8 +231,11 @@ impl std::fmt::Display for Value {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Value::Null => write!(f, "null"),
-			Value::Return(value) => write!(f, "{value}"),
 			Value::Int(int) => write!(f, "{int}"),
+			Value::BigInt(int) => write!(f, "{int}"),
+			// Debug formatting keeps the `.0` on whole numbers, so the output reads back as a float.
+			// The exceptions are `inf`, `-inf` and `NaN`, which Monkey has no literals for.
+			Value::Float(float) => write!(f, "{float:?}"),
 			Value::Bool(bool) => write!(f, "{bool}"),
 			Value::String(string) => write!(f, "{string}"),
 			Value::Array(values) => {
@@ -58,15 +250,18 @@ impl std::fmt::Display for Value {
 				)
 			}
 			Value::Map(map) => {
-				write!(f, "{{\n")?;
+				writeln!(f, "{{")?;
 
 				for (k, v) in map {
-					write!(f, "  {k} => {v},\n")?;
+					writeln!(f, "  {k} => {v},")?;
 				}
 
 				write!(f, "}}")
 			}
-			Value::Function { parameters, body, .. } => {
+			Value::Range { start, end, inclusive: false } => write!(f, "{start}..{end}"),
+			Value::Range { start, end, inclusive: true } => write!(f, "{start}..={end}"),
+			Value::Function(function) => {
+				let Function { parameters, body, .. } = &**function;
 				write!(
 					f,
 					"fn({}) {{\n  {}\n}}",
@@ -80,6 +275,7 @@ impl std::fmt::Display for Value {
 			Value::BuiltinFunction(function) => {
 				write!(f, "{}", function.name())
 			}
+			Value::Error(error) => write!(f, "error({error})"),
 		}
 	}
 }